@@ -0,0 +1,222 @@
+//! Plays many seeded runs per configuration and compares the outcomes.
+//!
+//! Every configuration of `balance.ron` plays the same seeds, so the
+//! differences come from the configuration and not from luck. Prints CSV
+//! (`configuration,metric,id,value` rows) or JSON to stdout.
+//!
+//! Usage: `cargo run --release --bin balance -- [--config balance.ron]
+//! [--runs N] [--seed S] [--max-minutes M] [--format csv|json]`
+
+use std::{collections::BTreeMap, path::Path, process::ExitCode, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use bevy_jam_5::{
+    game::{circle_sectors::EnemyHardness, loot::RewardId},
+    sim::{
+        bot::{GreedyBot, SectorPlacement},
+        simulate, SimConfig, SimOutcome, SimReport,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+struct BalanceConfig {
+    configurations: Vec<Configuration>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Configuration {
+    name: String,
+    #[serde(default)]
+    start: Vec<RewardId>,
+    #[serde(default)]
+    hardness: Option<Vec<f32>>,
+    #[serde(default)]
+    sector_placement: SectorPlacement,
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigurationReport {
+    name: String,
+    runs: usize,
+    win_rate: f32,
+    timeout_rate: f32,
+    /// Average cycle reached by lost runs.
+    average_death_cycle: Option<f32>,
+    /// Average battle length in seconds, by enemy id.
+    time_to_kill: BTreeMap<String, f32>,
+    /// By item and spell id.
+    content: BTreeMap<String, ContentReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct ContentReport {
+    /// Share of runs it was equipped in.
+    usage: f32,
+    win_rate_with: Option<f32>,
+    win_rate_without: Option<f32>,
+}
+
+fn arg<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::args()
+        .skip_while(|arg| arg != name)
+        .nth(1)
+        .and_then(|value| value.parse().ok())
+}
+
+fn main() -> ExitCode {
+    let config_path = arg::<String>("--config").unwrap_or_else(|| {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("balance.ron")
+            .display()
+            .to_string()
+    });
+    let runs = arg::<u64>("--runs").unwrap_or(100);
+    let seed = arg::<u64>("--seed").unwrap_or(0);
+    let format = arg::<String>("--format").unwrap_or_else(|| "csv".to_string());
+    let max_minutes = arg::<u64>("--max-minutes");
+
+    let balance_config = match std::fs::read_to_string(&config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| ron::from_str::<BalanceConfig>(&text).map_err(|e| e.to_string()))
+    {
+        Ok(balance_config) => balance_config,
+        Err(e) => {
+            eprintln!("error: {config_path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let reports = balance_config
+        .configurations
+        .iter()
+        .map(|configuration| {
+            let mut sim_config = SimConfig {
+                start: configuration.start.clone(),
+                hardness: configuration
+                    .hardness
+                    .clone()
+                    .map(EnemyHardness)
+                    .unwrap_or_default(),
+                ..SimConfig::default()
+            };
+            if let Some(minutes) = max_minutes {
+                sim_config.max_time = Duration::from_secs(minutes * 60);
+            }
+            let bot = GreedyBot {
+                sector_placement: configuration.sector_placement,
+            };
+
+            let sim_reports = (0..runs)
+                .map(|run| {
+                    eprintln!("{}: run {}/{runs}", configuration.name, run + 1);
+                    sim_config.seed = Some(seed.wrapping_add(run));
+                    simulate(&sim_config, bot)
+                })
+                .collect::<Vec<_>>();
+            aggregate(&configuration.name, &sim_reports)
+        })
+        .collect::<Vec<_>>();
+
+    match format.as_str() {
+        "json" => match serde_json::to_string_pretty(&reports) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        "csv" => print_csv(&reports),
+        _ => {
+            eprintln!("error: unknown format '{format}', expected csv or json");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn win_rate<'a>(reports: impl Iterator<Item = &'a SimReport>) -> Option<f32> {
+    let (runs, wins) = reports.fold((0, 0), |(runs, wins), report| {
+        (runs + 1, wins + (report.outcome == SimOutcome::Win) as u32)
+    });
+    (runs != 0).then(|| wins as f32 / runs as f32)
+}
+
+fn average(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (count, sum) = values.fold((0, 0.0), |(count, sum), value| (count + 1, sum + value));
+    (count != 0).then(|| sum / count as f32)
+}
+
+fn aggregate(name: &str, reports: &[SimReport]) -> ConfigurationReport {
+    let runs = reports.len();
+    let share = |count: usize| count as f32 / runs.max(1) as f32;
+
+    let mut kills = BTreeMap::<&str, Vec<f32>>::new();
+    for (id, time) in reports.iter().flat_map(|report| report.kills.iter()) {
+        kills.entry(id).or_default().push(*time);
+    }
+
+    let mut content = BTreeMap::new();
+    for id in reports.iter().flat_map(|report| report.equipped.iter()) {
+        if content.contains_key(id) {
+            continue;
+        }
+        let used = |report: &&SimReport| report.equipped.contains(id);
+        content.insert(
+            id.clone(),
+            ContentReport {
+                usage: share(reports.iter().filter(used).count()),
+                win_rate_with: win_rate(reports.iter().filter(used)),
+                win_rate_without: win_rate(reports.iter().filter(|report| !used(report))),
+            },
+        );
+    }
+
+    ConfigurationReport {
+        name: name.to_string(),
+        runs,
+        win_rate: win_rate(reports.iter()).unwrap_or(0.0),
+        timeout_rate: share(
+            reports
+                .iter()
+                .filter(|report| report.outcome == SimOutcome::Timeout)
+                .count(),
+        ),
+        average_death_cycle: average(
+            reports
+                .iter()
+                .filter(|report| report.outcome == SimOutcome::Loss)
+                .map(|report| report.cycles as f32),
+        ),
+        time_to_kill: kills
+            .into_iter()
+            .filter_map(|(id, times)| Some((id.to_string(), average(times.into_iter())?)))
+            .collect(),
+        content,
+    }
+}
+
+fn print_csv(reports: &[ConfigurationReport]) {
+    println!("configuration,metric,id,value");
+    for report in reports {
+        let name = &report.name;
+        println!("{name},runs,,{}", report.runs);
+        println!("{name},win_rate,,{}", report.win_rate);
+        println!("{name},timeout_rate,,{}", report.timeout_rate);
+        if let Some(cycle) = report.average_death_cycle {
+            println!("{name},average_death_cycle,,{cycle}");
+        }
+        for (id, time) in report.time_to_kill.iter() {
+            println!("{name},time_to_kill,{id},{time}");
+        }
+        for (id, content) in report.content.iter() {
+            println!("{name},usage,{id},{}", content.usage);
+            if let Some(rate) = content.win_rate_with {
+                println!("{name},win_rate_with,{id},{rate}");
+            }
+            if let Some(rate) = content.win_rate_without {
+                println!("{name},win_rate_without,{id},{rate}");
+            }
+        }
+    }
+}
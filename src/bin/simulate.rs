@@ -0,0 +1,49 @@
+//! Plays runs headless with the greedy bot and prints how they went.
+//!
+//! Usage: `cargo run --release --bin simulate -- [--runs N] [--seed S] [--max-minutes M]`
+//!
+//! Runs are seeded `S`, `S + 1`, ... so a bad run can be replayed in the
+//! game with `--seed`.
+
+use std::time::Duration;
+
+use bevy_jam_5::sim::{bot::GreedyBot, simulate, SimConfig};
+
+fn arg<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::args()
+        .skip_while(|arg| arg != name)
+        .nth(1)
+        .and_then(|value| value.parse().ok())
+}
+
+fn main() {
+    let runs = arg::<u64>("--runs").unwrap_or(1);
+    let seed = arg::<u64>("--seed");
+    let mut config = SimConfig::default();
+    if let Some(minutes) = arg::<u64>("--max-minutes") {
+        config.max_time = Duration::from_secs(minutes * 60);
+    }
+
+    for run in 0..runs {
+        config.seed = seed.map(|seed| seed.wrapping_add(run));
+        let report = simulate(&config, GreedyBot::default());
+
+        println!(
+            "seed {}: {:?} at cycle {} after {:.0}s, {} enemies killed, {} chests opened",
+            report.seed,
+            report.outcome,
+            report.cycles,
+            report.duration.as_secs_f32(),
+            report.enemies_killed,
+            report.chests_opened,
+        );
+        let hp = report
+            .hp
+            .iter()
+            .step_by(10)
+            .map(|(time, hp)| format!("{time:.0}s:{hp:.0}"))
+            .collect::<Vec<_>>();
+        println!("  hp: {}", hp.join(" "));
+        println!("  loot: {}", report.loot.join(" "));
+    }
+}
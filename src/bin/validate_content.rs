@@ -0,0 +1,262 @@
+//! Checks the content catalogs without starting the game.
+//!
+//! Every catalog is parsed and checked the same way the game loads it, then
+//! checked for unknown ids, missing asset files, weights outside of [0, 1]
+//! and content nothing can ever drop or spawn. Exits with a non-zero code on
+//! problems.
+//!
+//! Usage: `cargo run --bin validate_content [assets folder]`
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use serde::de::DeserializeOwned;
+
+use bevy_jam_5::game::{
+    chest::{ChestsAsset, CHESTS_DATA},
+    circle_sectors::{SectorSpawnId, SectorsAsset, DEFAULT_SECTOR_ID, SECTORS_DATA},
+    data::CheckContent,
+    enemy::{EnemiesAsset, BOSS_ENEMY_ID, ENEMIES_DATA},
+    items::{ItemsAsset, ITEMS_DATA},
+    loot::{Loot, LootTable, RewardId},
+    spells::{SpellsAsset, SPELLS_DATA},
+};
+
+fn main() -> ExitCode {
+    let assets = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("assets"));
+
+    let mut report = Report {
+        assets,
+        problems: vec![],
+    };
+    validate(&mut report);
+
+    for problem in report.problems.iter() {
+        eprintln!("error: {problem}");
+    }
+    if report.problems.is_empty() {
+        println!("content is valid");
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("found {} problem(s)", report.problems.len());
+        ExitCode::FAILURE
+    }
+}
+
+struct Report {
+    assets: PathBuf,
+    problems: Vec<String>,
+}
+
+impl Report {
+    fn error(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    /// Parses a catalog and runs the checks the game does when loading it.
+    fn load<A: DeserializeOwned + CheckContent>(&mut self, path: &str) -> Option<A> {
+        let bytes = match std::fs::read(self.assets.join(path)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.error(format!("{path}: could not read content file: {e}"));
+                return None;
+            }
+        };
+        match ron::de::from_bytes::<A>(&bytes) {
+            Ok(asset) => {
+                for problem in asset.problems() {
+                    self.error(format!("{path}: {problem}"));
+                }
+                Some(asset)
+            }
+            Err(e) => {
+                self.error(format!("{path}: could not parse content file: {e}"));
+                None
+            }
+        }
+    }
+
+    fn check_file(&mut self, owner: &str, path: &str) {
+        if !self.assets.join(path).is_file() {
+            self.error(format!("{owner} references missing file '{path}'"));
+        }
+    }
+
+    fn check_rate(&mut self, owner: &str, what: &str, rate: f32) {
+        if !(0.0..=1.0).contains(&rate) {
+            self.error(format!("{owner} has {what} {rate} outside of [0, 1]"));
+        }
+    }
+
+    /// Collects ids of one catalog, reporting duplicates.
+    fn ids<'a>(&mut self, kind: &str, ids: impl Iterator<Item = &'a str>) -> HashSet<&'a str> {
+        let mut set = HashSet::new();
+        for id in ids {
+            if !set.insert(id) {
+                self.error(format!("duplicate {kind} id '{id}'"));
+            }
+        }
+        set
+    }
+
+    fn check_id(&mut self, owner: &str, kind: &str, ids: &HashSet<&str>, id: &str) {
+        if !ids.contains(id) {
+            self.error(format!("{owner} references unknown {kind} id '{id}'"));
+        }
+    }
+
+    /// Checks weights of `table` and calls `on_drop` for every drop in it.
+    /// Drops behind a zero weight are passed as not reachable.
+    fn check_table<T>(
+        &mut self,
+        owner: &str,
+        table: &LootTable<T>,
+        reachable: bool,
+        on_drop: &mut dyn FnMut(&mut Self, &T, bool),
+    ) {
+        self.check_rate(owner, "weight of nothing", table.nothing);
+        for loot in table.guaranteed.iter() {
+            self.check_loot(owner, loot, reachable, on_drop);
+        }
+        for entry in table.entries.iter() {
+            self.check_rate(owner, "weight", entry.weight);
+            let reachable = reachable && table.rolls > 0 && entry.weight > 0.0;
+            self.check_loot(owner, &entry.loot, reachable, on_drop);
+        }
+    }
+
+    fn check_loot<T>(
+        &mut self,
+        owner: &str,
+        loot: &Loot<T>,
+        reachable: bool,
+        on_drop: &mut dyn FnMut(&mut Self, &T, bool),
+    ) {
+        match loot {
+            Loot::Drop(drop) => on_drop(self, drop, reachable),
+            Loot::Table(table) => self.check_table(owner, table, reachable, on_drop),
+        }
+    }
+}
+
+fn validate(report: &mut Report) {
+    let items = report.load::<ItemsAsset>(ITEMS_DATA);
+    let spells = report.load::<SpellsAsset>(SPELLS_DATA);
+    let enemies = report.load::<EnemiesAsset>(ENEMIES_DATA);
+    let chests = report.load::<ChestsAsset>(CHESTS_DATA);
+    let sectors = report.load::<SectorsAsset>(SECTORS_DATA);
+    // Catalogs reference each other, without all of them ids can't be checked.
+    let (Some(items), Some(spells), Some(enemies), Some(chests), Some(sectors)) =
+        (items, spells, enemies, chests, sectors)
+    else {
+        return;
+    };
+
+    let item_ids = report.ids("item", items.items.iter().map(|i| i.id.as_str()));
+    let spell_ids = report.ids("spell", spells.spells.iter().map(|s| s.id.as_str()));
+    let enemy_ids = report.ids("enemy", enemies.enemies.iter().map(|e| e.id.as_str()));
+    let chest_ids = report.ids("chest", chests.chests.iter().map(|c| c.id.as_str()));
+    let sector_ids = report.ids("sector", sectors.sectors.iter().map(|s| s.id.as_str()));
+
+    report.check_id("the game", "enemy", &enemy_ids, BOSS_ENEMY_ID);
+    report.check_id("the game", "sector", &sector_ids, DEFAULT_SECTOR_ID);
+
+    for item in items.items.iter() {
+        report.check_file(&item.id, &item.image);
+    }
+    // Spell cooldowns, chances, strikes and status effects are checked when loading.
+    for spell in spells.spells.iter() {
+        report.check_file(&spell.id, &spell.image);
+    }
+
+    // Content some source can actually drop or spawn.
+    let mut dropped_items = HashSet::<String>::new();
+    let mut dropped_spells = HashSet::<String>::new();
+    let mut dropped_sectors = HashSet::from([DEFAULT_SECTOR_ID.to_string()]);
+    let mut spawned_enemies = HashSet::from([BOSS_ENEMY_ID.to_string()]);
+    let mut spawned_chests = HashSet::<String>::new();
+    // Spells only enemies cast don't need to drop.
+    let mut cast_spells = HashSet::<String>::new();
+
+    let mut on_reward = |report: &mut Report, owner: &str, reward: &RewardId, reachable: bool| {
+        let (kind, ids, dropped, id) = match reward {
+            RewardId::Item(id) => ("item", &item_ids, &mut dropped_items, id),
+            RewardId::Spell(id) => ("spell", &spell_ids, &mut dropped_spells, id),
+            RewardId::Sector(id) => ("sector", &sector_ids, &mut dropped_sectors, id),
+        };
+        report.check_id(owner, kind, ids, id);
+        if reachable {
+            dropped.insert(id.clone());
+        }
+    };
+
+    // Enemy animations, spell cooldowns and cast conditions are checked when loading.
+    for enemy in enemies.enemies.iter() {
+        for sheet in [&enemy.idle, &enemy.attack, &enemy.dead] {
+            report.check_file(&enemy.id, &sheet.texture);
+        }
+        report.check_table(
+            &enemy.id,
+            &enemy.loot,
+            true,
+            &mut |report, reward, reachable| on_reward(report, &enemy.id, reward, reachable),
+        );
+        for enemy_spell in enemy.spells.iter() {
+            report.check_id(&enemy.id, "spell", &spell_ids, &enemy_spell.spell);
+            cast_spells.insert(enemy_spell.spell.clone());
+        }
+    }
+    for chest in chests.chests.iter() {
+        report.check_table(
+            &chest.id,
+            &chest.loot,
+            true,
+            &mut |report, reward, reachable| on_reward(report, &chest.id, reward, reachable),
+        );
+    }
+    for sector in sectors.sectors.iter() {
+        report.check_file(&sector.id, &sector.background);
+        report.check_file(&sector.id, &sector.card);
+        report.check_table(
+            &sector.id,
+            &sector.spawns,
+            true,
+            &mut |report, spawn, reachable| {
+                let (kind, ids, spawned, id) = match spawn {
+                    SectorSpawnId::Enemy(id) => ("enemy", &enemy_ids, &mut spawned_enemies, id),
+                    SectorSpawnId::Chest(id) => ("chest", &chest_ids, &mut spawned_chests, id),
+                };
+                report.check_id(&sector.id, kind, ids, id);
+                if reachable {
+                    spawned.insert(id.clone());
+                }
+            },
+        );
+    }
+
+    dropped_spells.extend(cast_spells);
+
+    let unreachable = [
+        ("item", "dropped", &item_ids, &dropped_items),
+        ("spell", "dropped", &spell_ids, &dropped_spells),
+        ("sector", "dropped", &sector_ids, &dropped_sectors),
+        ("enemy", "spawned", &enemy_ids, &spawned_enemies),
+        ("chest", "spawned", &chest_ids, &spawned_chests),
+    ];
+    for (kind, how, ids, reachable) in unreachable {
+        let mut ids = ids
+            .iter()
+            .filter(|id| !reachable.contains(**id))
+            .collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            report.error(format!("{kind} '{id}' is never {how}"));
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    circle_sectors::{SectorIdx, SectorPlacedEvent, SectorPosition},
+    inventory::{
+        Inventory, InventoryUpdateEvent, INVENTORY_BACKPACK_ITEMS, INVENTORY_BACKPACK_SECTORS,
+        INVENTORY_ITEMS,
+    },
+    sound::{SoundCue, SoundCueEvent},
+    spells::CastSpellEvent,
+    GameState,
+};
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerActionEvent>().add_systems(
+            PreUpdate,
+            apply_player_actions.run_if(state_exists::<GameState>),
+        );
+    }
+}
+
+/// Every decision the player makes during a run. The ui, bots and replays
+/// all go through these, so they play the same game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerAction {
+    /// Moves a backpack item into the active items.
+    EquipItem(usize),
+    /// Moves a backpack spell into the active spells.
+    EquipSpell(usize),
+    /// Casts an active spell.
+    CastSpell(usize),
+    /// Places a backpack sector on the clock.
+    PlaceSector {
+        backpack: usize,
+        position: u8,
+    },
+    TogglePause,
+}
+
+impl PlayerAction {
+    /// Returns false if the action points outside of the inventory slots.
+    pub fn in_bounds(&self) -> bool {
+        match *self {
+            PlayerAction::EquipItem(i) | PlayerAction::EquipSpell(i) => {
+                i < INVENTORY_BACKPACK_ITEMS
+            }
+            PlayerAction::CastSpell(i) => i < INVENTORY_ITEMS,
+            PlayerAction::PlaceSector { backpack, .. } => backpack < INVENTORY_BACKPACK_SECTORS,
+            PlayerAction::TogglePause => true,
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayerActionEvent(pub PlayerAction);
+
+/// Runs before `Update`, so actions take effect at the same point of a
+/// frame whichever system sent them.
+pub fn apply_player_actions(
+    game_state: Res<State<GameState>>,
+    mut game_state_next: ResMut<NextState<GameState>>,
+    mut inventory: ResMut<Inventory>,
+    mut sectors: Query<(&SectorPosition, &mut SectorIdx)>,
+    mut event_reader: EventReader<PlayerActionEvent>,
+    mut cast_spell_event: EventWriter<CastSpellEvent>,
+    mut sector_placed_event: EventWriter<SectorPlacedEvent>,
+    mut inventory_update_event: EventWriter<InventoryUpdateEvent>,
+    mut sound_event: EventWriter<SoundCueEvent>,
+    mut unpaused_state: Local<GameState>,
+) {
+    for e in event_reader.read() {
+        match e.0 {
+            PlayerAction::EquipItem(i) => {
+                if inventory.backpack_items.get(i).is_some() {
+                    sound_event.send(SoundCueEvent(SoundCue::ItemEquip));
+                }
+                inventory.equip_item(i);
+                inventory_update_event.send(InventoryUpdateEvent);
+            }
+            PlayerAction::EquipSpell(i) => {
+                if inventory.backpack_spells.get(i).is_some() {
+                    sound_event.send(SoundCueEvent(SoundCue::ItemEquip));
+                }
+                inventory.equip_spell(i);
+                inventory_update_event.send(InventoryUpdateEvent);
+            }
+            PlayerAction::CastSpell(i) => {
+                if let Some(spell_idx) = inventory.get_spell_idx(i) {
+                    cast_spell_event.send(CastSpellEvent(spell_idx));
+                }
+            }
+            PlayerAction::PlaceSector { backpack, position } => {
+                let Some(sector_idx) = inventory.backpack_sectors.get(backpack) else {
+                    continue;
+                };
+                let Some((_, mut current_sector_idx)) =
+                    sectors.iter_mut().find(|(p, _)| p.0 == position)
+                else {
+                    continue;
+                };
+                *current_sector_idx = sector_idx;
+                inventory.backpack_sectors.remove(backpack);
+                sector_placed_event.send(SectorPlacedEvent);
+                inventory_update_event.send(InventoryUpdateEvent);
+            }
+            PlayerAction::TogglePause => {
+                if game_state.get() == &GameState::Paused {
+                    game_state_next.set(*unpaused_state);
+                } else {
+                    *unpaused_state = *game_state.get();
+                    game_state_next.set(GameState::Paused);
+                }
+            }
+        }
+    }
+}
@@ -4,24 +4,31 @@ use bevy::prelude::*;
 
 use crate::{ui::UiStyle, GlobalState};
 
-use super::GameState;
+use super::{GameState, GameplaySet};
 
 pub const DAMAGE_COLOR_DEFAULT: Color = Color::srgb(1.0, 0.0, 0.0);
-pub const DAMAGE_COLOR_KEYBOARD: Color = Color::srgb(0.5, 0.5, 0.5);
-pub const DAMAGE_COLOR_MARKER: Color = Color::srgb(52.0 / 255.0, 52.0 / 255.0, 209.0 / 255.0);
-pub const DAMAGE_COLOR_FIRE_PUNCH: Color = Color::srgb(209.0 / 255.0, 115.0 / 255.0, 46.0 / 255.0);
+/// Damage taken by a `Shield`.
+pub const DAMAGE_COLOR_SHIELD: Color = Color::srgb(0.4, 0.8, 1.0);
+
+const DAMAGE_TEXT_SIZE: f32 = 30.0;
+const DAMAGE_TEXT_SIZE_CRIT: f32 = 45.0;
 
 pub struct AnimationPlugin;
 
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<AnimationFinishedEvent>().add_systems(
-            Update,
-            (run_sprite_animations, run_damage_text_animations).run_if(
-                in_state(GameState::Running)
-                    .or_else(in_state(GameState::Battle).or_else(in_state(GameState::Pickup))),
-            ),
-        );
+        let playing = || {
+            in_state(GameState::Running)
+                .or_else(in_state(GameState::Battle).or_else(in_state(GameState::Pickup)))
+        };
+        app.add_event::<AnimationFinishedEvent>()
+            .add_systems(
+                FixedUpdate,
+                run_sprite_animations
+                    .run_if(playing())
+                    .in_set(GameplaySet::Tick),
+            )
+            .add_systems(Update, run_damage_text_animations.run_if(playing()));
     }
 }
 
@@ -88,14 +95,20 @@ pub fn spawn_damage_text(
     transform: Transform,
     direction: Vec3,
     color: Color,
+    crit: bool,
 ) {
+    let font_size = if crit {
+        DAMAGE_TEXT_SIZE_CRIT
+    } else {
+        DAMAGE_TEXT_SIZE
+    };
     commands.spawn((
         Text2dBundle {
             text: Text::from_section(
                 format!("{:.1}", damage),
                 TextStyle {
                     font: ui_style.text_style.font.clone(),
-                    font_size: 30.0,
+                    font_size,
                     color,
                 },
             ),
@@ -1,29 +1,41 @@
 use std::ops::{Index, IndexMut};
 
-use bevy::{audio::PlaybackMode, ecs::system::EntityCommands, prelude::*};
-use rand::Rng;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use serde::Deserialize;
 
 use crate::GlobalState;
 
 use super::{
     animation::{AllAnimations, AnimationConfig, AnimationFinishedEvent},
-    circle_sectors::{SectorIdx, SectorPosition, Sectors},
-    inventory::{Inventory, InventoryUpdateEvent},
-    items::{ItemIdx, Items},
-    sound::SoundResources,
-    spells::{SpellIdx, Spells},
-    GameState,
+    circle_sectors::SectorPosition,
+    data::{
+        build_content_ids, CheckContent, ContentAsset, ContentHandle, ContentIds,
+        ContentLoadedEvent, RonAssetLoader,
+    },
+    inventory::{Inventory, InventoryUpdateEvent, LootCollectedEvent},
+    loot::{resolve_rewards, LootTable, Reward, RewardId},
+    rng::RunRng,
+    sound::{SoundCue, SoundCueEvent},
+    GameState, GameplaySet,
 };
 
+/// Chest catalog, relative to the assets folder.
+pub const CHESTS_DATA: &str = "data/chests.ron";
+
 pub struct ChestsPlugin;
 
 impl Plugin for ChestsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ChestOppenedEvent>()
+            .init_asset::<ChestsAsset>()
+            .register_asset_loader(RonAssetLoader::<ChestsAsset>::new(&["chests.ron"]))
             .add_systems(Startup, prepare_chest_resources)
+            .add_systems(Update, build_chests.after(build_content_ids))
             .add_systems(
-                Update,
-                (chest_open_check, on_chest_open_finish).run_if(in_state(GameState::Pickup)),
+                FixedUpdate,
+                (chest_open_check, on_chest_open_finish)
+                    .run_if(in_state(GameState::Pickup))
+                    .in_set(GameplaySet::Check),
             );
     }
 }
@@ -47,12 +59,32 @@ pub struct Chest;
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct InteractedChest;
 
+/// Single entry of the `assets/data/chests.ron` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChestData {
+    pub id: String,
+    #[serde(default)]
+    pub loot: LootTable<RewardId>,
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ChestsAsset {
+    pub chests: Vec<ChestData>,
+}
+
+impl CheckContent for ChestsAsset {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for chest_data in self.chests.iter() {
+            chest_data.loot.check(&chest_data.id, &mut problems);
+        }
+        problems
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ChestInfo {
-    pub spawn_rate: f32,
-    pub items: Vec<ItemIdx>,
-    pub spells: Vec<SpellIdx>,
-    pub sectors: Vec<SectorIdx>,
+    pub loot: LootTable<Reward>,
 }
 
 #[derive(Resource, Debug, Clone, PartialEq)]
@@ -91,53 +123,30 @@ fn prepare_chest_resources(
         texture_atlas,
     });
 
-    let mut chests = Chests(vec![]);
-    // 0 Default
-    chests.0.push(ChestInfo {
-        spawn_rate: 0.25,
-        // Paperclip, Stickynotes
-        items: vec![ItemIdx(1), ItemIdx(5)],
-        // Stand up
-        spells: vec![SpellIdx(5)],
-        // Green, Yellow
-        sectors: vec![SectorIdx(1), SectorIdx(2)],
-    });
-    // 1 Green
-    chests.0.push(ChestInfo {
-        spawn_rate: 0.25,
-        // Paperclip, Plant, Scissors,
-        items: vec![ItemIdx(1), ItemIdx(2), ItemIdx(3)],
-        // Excel
-        spells: vec![SpellIdx(4)],
-        sectors: vec![SectorIdx(2)],
-    });
-    // 2 Yellow
-    chests.0.push(ChestInfo {
-        spawn_rate: 0.25,
-        // Coffee, Stickynotes
-        items: vec![ItemIdx(0), ItemIdx(5)],
-        // Lunchbox
-        spells: vec![SpellIdx(3)],
-        sectors: vec![SectorIdx(3), SectorIdx(4)],
-    });
-    // 3 Grey
-    chests.0.push(ChestInfo {
-        spawn_rate: 0.3,
-        // Coffee, Paperclip, Scissors
-        items: vec![ItemIdx(0), ItemIdx(1), ItemIdx(3)],
-        // Marker, Keyboard, Powerpoint
-        spells: vec![SpellIdx(0), SpellIdx(1), SpellIdx(6)],
-        sectors: vec![SectorIdx(3)],
-    });
-    // 4 Brown
-    chests.0.push(ChestInfo {
-        spawn_rate: 0.3,
-        // Coffee, Scissors, Stapler
-        items: vec![ItemIdx(0), ItemIdx(3), ItemIdx(4)],
-        spells: vec![SpellIdx(2), SpellIdx(3), SpellIdx(4)],
-        sectors: vec![SectorIdx(3), SectorIdx(4)],
-    });
-    commands.insert_resource(chests);
+    commands.insert_resource(ContentHandle::<ChestsAsset>(asset_server.load(CHESTS_DATA)));
+    // Filled by `build_chests` once the data file is loaded.
+    commands.insert_resource(Chests(vec![]));
+}
+
+fn build_chests(
+    content_ids: Res<ContentIds>,
+    chests_asset: ContentAsset<ChestsAsset>,
+    mut chests: ResMut<Chests>,
+    mut event_reader: EventReader<ContentLoadedEvent>,
+) {
+    for _ in event_reader.read() {
+        let Some(chests_asset) = chests_asset.get() else {
+            continue;
+        };
+
+        chests.0 = chests_asset
+            .chests
+            .iter()
+            .map(|chest_data| ChestInfo {
+                loot: resolve_rewards(&content_ids, &chest_data.id, &chest_data.loot),
+            })
+            .collect();
+    }
 }
 
 pub fn spawn_chest<'a>(
@@ -179,29 +188,20 @@ fn chest_open_check(
 }
 
 fn on_chest_open_finish(
-    items: Res<Items>,
     chests: Res<Chests>,
-    spells: Res<Spells>,
-    sectors: Res<Sectors>,
-    sounds: Res<SoundResources>,
     chest: Query<(Entity, &ChestIdx), With<InteractedChest>>,
     mut commands: Commands,
     mut inventory: ResMut<Inventory>,
+    mut run_rng: ResMut<RunRng>,
     mut inventory_update_event: EventWriter<InventoryUpdateEvent>,
+    mut loot_event: EventWriter<LootCollectedEvent>,
     mut chest_openned_event: EventWriter<ChestOppenedEvent>,
+    mut sound_event: EventWriter<SoundCueEvent>,
     mut event_reader: EventReader<AnimationFinishedEvent>,
 ) {
     for e in event_reader.read() {
         if e.0 == AllAnimations::ChestOpen {
-            // Chest open sound
-            commands.spawn(AudioBundle {
-                source: sounds.chest_open.clone(),
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: sounds.volume,
-                    ..Default::default()
-                },
-            });
+            sound_event.send(SoundCueEvent(SoundCue::ChestOpen));
 
             let Ok((chest_entity, chest_idx)) = chest.get_single() else {
                 return;
@@ -213,34 +213,9 @@ fn on_chest_open_finish(
                 .despawn_recursive();
 
             let chest_info = &chests[*chest_idx];
-
-            let mut thread_rng = rand::thread_rng();
-
-            if !chest_info.items.is_empty() {
-                let random_item_idx =
-                    chest_info.items[thread_rng.gen_range(0..chest_info.items.len())];
-                let item = &items[random_item_idx];
-                if thread_rng.gen_bool(item.drop_rate as f64) {
-                    inventory.backpack_items.push(random_item_idx);
-                }
-            }
-
-            if !chest_info.spells.is_empty() {
-                let random_spell_idx =
-                    chest_info.spells[thread_rng.gen_range(0..chest_info.spells.len())];
-                let spell = &spells[random_spell_idx];
-                if thread_rng.gen_bool(spell.drop_rate as f64) {
-                    inventory.backpack_spells.push(random_spell_idx);
-                }
-            }
-
-            if !chest_info.sectors.is_empty() {
-                let random_sector_idx =
-                    chest_info.sectors[thread_rng.gen_range(0..chest_info.sectors.len())];
-                let sector = &sectors[random_sector_idx];
-                if thread_rng.gen_bool(sector.drop_rate as f64) {
-                    inventory.backpack_sectors.push(random_sector_idx);
-                }
+            for reward in chest_info.loot.roll(&mut run_rng.loot) {
+                inventory.add_reward(reward);
+                loot_event.send(LootCollectedEvent(reward));
             }
 
             info!("chest open event");
@@ -1,9 +1,13 @@
 use bevy::{
-    ecs::component::{ComponentHooks, StorageType},
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        system::EntityCommands,
+    },
     prelude::*,
     sprite::MaterialMesh2dBundle,
 };
 use rand::Rng;
+use serde::Deserialize;
 use std::{
     f32::consts::*,
     ops::{Index, IndexMut},
@@ -18,17 +22,30 @@ use crate::{
 };
 
 use super::{
-    chest::{spawn_chest, ChestIdx, ChestResources, Chests},
+    actions::{PlayerAction, PlayerActionEvent},
+    chest::{spawn_chest, ChestIdx, ChestResources},
     cursor::CursorSector,
-    enemy::{spawn_enemy, Enemies, Enemy, EnemyIdx},
+    data::{
+        build_content_ids, srgb_u8, CheckContent, ContentAsset, ContentHandle, ContentIds,
+        ContentLoadedEvent, RonAssetLoader,
+    },
+    enemy::{spawn_enemy, Enemies, Enemy, EnemyIdx, BOSS_ENEMY_ID},
     hp_bar::HpBarResources,
     inventory::Inventory,
-    GameState, Player, Z_CHEST, Z_CLOCK_ARROWS, Z_CLOCK_CENTER, Z_CLOCK_KNOB, Z_CLOCK_NUMBERS,
-    Z_ENEMY, Z_SECTORS, Z_SECTOR_BACKGROUND, Z_WALL,
+    loot::LootTable,
+    rng::{seed_run_rng, RunRng},
+    GameState, GameplaySet, Player, Z_CHEST, Z_CLOCK_ARROWS, Z_CLOCK_CENTER, Z_CLOCK_KNOB,
+    Z_CLOCK_NUMBERS, Z_ENEMY, Z_SECTORS, Z_SECTOR_BACKGROUND, Z_WALL,
 };
 
+/// Sector catalog, relative to the assets folder.
+pub const SECTORS_DATA: &str = "data/sectors.ron";
+
 pub const MAX_CYCLES: u8 = 8;
 
+/// Id of the sector every clock position starts with.
+pub const DEFAULT_SECTOR_ID: &str = "default";
+
 pub const CIRCLE_RADIUS: f32 = 200.0;
 pub const CIRCLE_INNER_RADIUS: f32 = 180.0;
 
@@ -47,18 +64,30 @@ impl Plugin for SectorsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SectorPlacedEvent>()
             .add_event::<LastCycleEvent>()
+            .add_event::<HourCompleteEvent>()
+            .init_resource::<EnemyHardness>()
+            .init_asset::<SectorsAsset>()
+            .register_asset_loader(RonAssetLoader::<SectorsAsset>::new(&["sectors.ron"]))
             .add_systems(PreStartup, prepare_sector_resources)
-            .add_systems(OnEnter(GlobalState::MainMenu), spawn_clock)
+            .add_systems(Update, build_sectors.after(build_content_ids))
+            .add_systems(
+                OnEnter(GlobalState::MainMenu),
+                spawn_clock.after(seed_run_rng),
+            )
             .add_systems(
                 Update,
+                (update_minute_arrow, update_hour_arrow).run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
                 (
-                    update_minute_arrow,
-                    update_hour_arrow,
                     update_player_progress,
                     on_last_cycle_event,
                     sector_spawn_things,
                 )
-                    .run_if(in_state(GameState::Running)),
+                    .chain()
+                    .run_if(in_state(GameState::Running))
+                    .in_set(GameplaySet::Spawn),
             )
             .add_systems(
                 Update,
@@ -87,12 +116,32 @@ pub struct PlayerProgress {
     pub player_last_sector: u8,
 }
 
+/// Multiplier of enemy hp and damage for each cycle, 1 past the end.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct EnemyHardness(pub Vec<f32>);
+
+impl Default for EnemyHardness {
+    fn default() -> Self {
+        Self(vec![1.0, 1.2, 1.3, 1.4, 1.5, 1.6, 1.7, 1.8])
+    }
+}
+
+impl EnemyHardness {
+    pub fn get(&self, cycles: u8) -> f32 {
+        self.0.get(cycles as usize).copied().unwrap_or(1.0)
+    }
+}
+
 #[derive(Event, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SectorPlacedEvent;
 
 #[derive(Event, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct LastCycleEvent;
 
+/// Sent when the player passes 12 o'clock.
+#[derive(Event, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HourCompleteEvent;
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct LastBossTag;
 
@@ -112,15 +161,55 @@ impl Component for LastBossTag {
 #[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SectorIdx(pub usize);
 
+/// Single entry of the `assets/data/sectors.ron` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SectorData {
+    pub id: String,
+    pub description: String,
+    #[serde(with = "srgb_u8")]
+    pub color: Color,
+    pub background: String,
+    pub card: String,
+    #[serde(default)]
+    pub spawns: LootTable<SectorSpawnId>,
+}
+
+/// Thing a sector can spawn in one of its slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectorSpawn {
+    Enemy(EnemyIdx),
+    Chest(ChestIdx),
+}
+
+/// `SectorSpawn` as written in the data files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum SectorSpawnId {
+    Enemy(String),
+    Chest(String),
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct SectorsAsset {
+    pub sectors: Vec<SectorData>,
+}
+
+impl CheckContent for SectorsAsset {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for sector_data in self.sectors.iter() {
+            sector_data.spawns.check(&sector_data.id, &mut problems);
+        }
+        problems
+    }
+}
+
 #[derive(Debug)]
 pub struct SectorInfo {
-    pub description: &'static str,
+    pub description: String,
     pub material: Handle<ColorMaterial>,
     pub background: Handle<Image>,
     pub card: Handle<Image>,
-    pub drop_rate: f32,
-    pub enemies: Vec<EnemyIdx>,
-    pub chests: Vec<ChestIdx>,
+    pub spawns: LootTable<SectorSpawn>,
 }
 
 #[derive(Resource, Debug)]
@@ -143,12 +232,12 @@ impl IndexMut<SectorIdx> for Sectors {
 pub struct SectorPosition(pub u8);
 
 #[derive(Component, Debug, Clone, PartialEq, Eq)]
-pub struct SectorTimer(Timer);
+pub struct SectorTimer(pub Timer);
 
-impl Default for SectorTimer {
-    fn default() -> Self {
+impl SectorTimer {
+    pub fn new(rng: &mut impl Rng) -> Self {
         // 1..2 seconds
-        let duration = 1.0 + rand::random::<f32>() * 1.0;
+        let duration = 1.0 + rng.gen::<f32>() * 1.0;
         Self(Timer::from_seconds(duration, TimerMode::Repeating))
     }
 }
@@ -168,6 +257,12 @@ pub struct SectorSlotEntity {
     slot_position: usize,
 }
 
+impl SectorSlotEntity {
+    pub fn slot_position(&self) -> usize {
+        self.slot_position
+    }
+}
+
 impl Component for SectorSlotEntity {
     const STORAGE_TYPE: StorageType = StorageType::Table;
 
@@ -241,61 +336,64 @@ fn prepare_sector_resources(
         wall_image,
     });
 
-    let mut sectors = Sectors(vec![]);
-    sectors.0.push(SectorInfo {
-        description: "Grey walls look at you.",
-        material: materials.add(Color::srgb_u8(174, 174, 169)),
-        background: asset_server.load("sectors/zone_default_bent.png"),
-        card: asset_server.load("sectors_cards/zone_default_card.png"),
-        drop_rate: 0.2,
-        enemies: vec![EnemyIdx(1)],
-        chests: vec![ChestIdx(0)],
-    });
-    sectors.0.push(SectorInfo {
-        description: "Green zeebras on walls",
-        material: materials.add(Color::srgb_u8(180, 195, 190)),
-        background: asset_server.load("sectors/zone_green_bent.png"),
-        card: asset_server.load("sectors_cards/zone_green_card.png"),
-        drop_rate: 0.4,
-        enemies: vec![EnemyIdx(1), EnemyIdx(3)],
-        chests: vec![ChestIdx(1)],
-    });
-    sectors.0.push(SectorInfo {
-        description: "Bright and blinding.",
-        material: materials.add(Color::srgb_u8(253, 252, 205)),
-        background: asset_server.load("sectors/zone_yellow_bent.png"),
-        card: asset_server.load("sectors_cards/zone_yellow_card.png"),
-        drop_rate: 0.3,
-        enemies: vec![EnemyIdx(1), EnemyIdx(3)],
-        chests: vec![ChestIdx(2)],
-    });
-    sectors.0.push(SectorInfo {
-        description: "Beer bottle glass.",
-        material: materials.add(Color::srgb_u8(125, 169, 157)),
-        background: asset_server.load("sectors/zone_grey_bent.png"),
-        card: asset_server.load("sectors_cards/zone_grey_card.png"),
-        drop_rate: 0.2,
-        enemies: vec![EnemyIdx(2), EnemyIdx(3)],
-        chests: vec![ChestIdx(3)],
-    });
-    sectors.0.push(SectorInfo {
-        description: "Inside the shroom.",
-        material: materials.add(Color::srgb_u8(128, 93, 71)),
-        background: asset_server.load("sectors/zone_brown_bent.png"),
-        card: asset_server.load("sectors_cards/zone_brown_card.png"),
-        drop_rate: 0.2,
-        enemies: vec![EnemyIdx(2), EnemyIdx(4)],
-        chests: vec![ChestIdx(4)],
-    });
-    commands.insert_resource(sectors);
+    commands.insert_resource(ContentHandle::<SectorsAsset>(
+        asset_server.load(SECTORS_DATA),
+    ));
+    // Filled by `build_sectors` once the data file is loaded.
+    commands.insert_resource(Sectors(vec![]));
+}
+
+fn build_sectors(
+    asset_server: Res<AssetServer>,
+    content_ids: Res<ContentIds>,
+    sectors_asset: ContentAsset<SectorsAsset>,
+    mut sectors: ResMut<Sectors>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut event_reader: EventReader<ContentLoadedEvent>,
+) {
+    for _ in event_reader.read() {
+        let Some(sectors_asset) = sectors_asset.get() else {
+            continue;
+        };
+
+        sectors.0 = sectors_asset
+            .sectors
+            .iter()
+            .map(|sector_data| SectorInfo {
+                description: sector_data.description.clone(),
+                material: materials.add(sector_data.color),
+                background: asset_server.load(sector_data.background.clone()),
+                card: asset_server.load(sector_data.card.clone()),
+                spawns: sector_data
+                    .spawns
+                    .filter_map(&mut |spawn_id| match spawn_id {
+                        SectorSpawnId::Enemy(id) => content_ids
+                            .enemies
+                            .resolve(&sector_data.id, id)
+                            .map(SectorSpawn::Enemy),
+                        SectorSpawnId::Chest(id) => content_ids
+                            .chests
+                            .resolve(&sector_data.id, id)
+                            .map(SectorSpawn::Chest),
+                    }),
+            })
+            .collect();
+    }
 }
 
 fn spawn_clock(
     sectors: Res<Sectors>,
+    content_ids: Res<ContentIds>,
     ui_style: Res<UiStyle>,
     sector_resources: Res<SectorResources>,
+    mut run_rng: ResMut<RunRng>,
     mut commands: Commands,
 ) {
+    let Some(default_sector_idx) = content_ids.sectors.get(DEFAULT_SECTOR_ID) else {
+        error!("no sector with id '{DEFAULT_SECTOR_ID}'");
+        return;
+    };
+
     commands.insert_resource(PlayerProgress {
         cycles: 0,
         player_last_sector: 0,
@@ -320,8 +418,8 @@ fn spawn_clock(
         // Rotation happens ccw, so make it cw.
         transform.rotate_local_z(-rotation);
 
-        let sector_idx = SectorIdx(0);
-        let sector_info = &sectors.0[sector_idx.0];
+        let sector_idx = default_sector_idx;
+        let sector_info = &sectors[sector_idx];
         let material = sector_info.material.clone();
         commands
             .spawn((
@@ -333,7 +431,7 @@ fn spawn_clock(
                 },
                 SectorPosition(i),
                 sector_idx,
-                SectorTimer::default(),
+                SectorTimer::new(&mut run_rng.spawn),
                 SectorSlots::default(),
                 StateScoped(GlobalState::InGame),
             ))
@@ -477,6 +575,7 @@ fn update_player_progress(
     player: Query<&Transform, With<Player>>,
     mut player_progress: ResMut<PlayerProgress>,
     mut event_writer: EventWriter<LastCycleEvent>,
+    mut hour_complete_event: EventWriter<HourCompleteEvent>,
 ) {
     let Ok(player_transform) = player.get_single() else {
         return;
@@ -487,6 +586,7 @@ fn update_player_progress(
     if sector_id != player_progress.player_last_sector {
         if sector_id == 0 {
             player_progress.cycles += 1;
+            hour_complete_event.send(HourCompleteEvent);
             if player_progress.cycles == MAX_CYCLES {
                 event_writer.send(LastCycleEvent);
             }
@@ -498,6 +598,7 @@ fn update_player_progress(
 
 fn on_last_cycle_event(
     enemies: Res<Enemies>,
+    content_ids: Res<ContentIds>,
     hp_bar_resources: Res<HpBarResources>,
     sector_enemies: Query<(Entity, &SectorPosition), With<Enemy>>,
     mut commands: Commands,
@@ -505,6 +606,11 @@ fn on_last_cycle_event(
     mut event_reader: EventReader<LastCycleEvent>,
 ) {
     for _ in event_reader.read() {
+        let Some(boss_idx) = content_ids.enemies.get(BOSS_ENEMY_ID) else {
+            error!("no enemy with id '{BOSS_ENEMY_ID}'");
+            continue;
+        };
+
         for (entity, sector_position) in sector_enemies.iter() {
             if sector_position.0 == SECTORS_NUM - 1 {
                 let Some(e) = commands.get_entity(entity) else {
@@ -521,26 +627,12 @@ fn on_last_cycle_event(
             }
         }
 
-        // Spawn big boss
-        let angle = sector_id_to_start_angle(SECTORS_NUM - 1) + SECTOR_ANGLE / 2.0
-            - SECTOR_THING_GAP / 2.0 * (SECTOR_THINGS - 1) as f32
-            + SECTOR_THING_GAP * 3.0;
-
-        let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 35.0, Z_ENEMY)
-            .with_scale(Vec3::new(2.5, 2.5, 2.5));
-        t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
-
-        spawn_enemy(
+        spawn_last_boss(
             &mut commands,
-            1.0,
             enemies.as_ref(),
-            EnemyIdx(0),
-            SectorPosition(SECTORS_NUM - 1),
+            boss_idx,
             hp_bar_resources.as_ref(),
-            t,
-            true,
-        )
-        .insert(LastBossTag);
+        );
     }
 }
 
@@ -551,9 +643,9 @@ fn sector_update_selected(
     selected_section_button: Res<SelectedSectionButton>,
     buttons: Query<&BackpackSectorId>,
     mouse_input: Res<ButtonInput<MouseButton>>,
-    mut sectors_bottom: Query<(&SectorPosition, &mut SectorIdx, &mut Handle<ColorMaterial>)>,
+    mut sectors_bottom: Query<(&SectorPosition, &mut Handle<ColorMaterial>), With<SectorIdx>>,
     mut sectors_background: Query<(&SectorPosition, &mut Handle<Image>)>,
-    mut event_writer: EventWriter<SectorPlacedEvent>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
 ) {
     let Some(cursor_sector_position) = cursor_sector.0 else {
         return;
@@ -573,12 +665,14 @@ fn sector_update_selected(
 
     let to_be_placed_sector_info = &sectors[sector_idx];
 
-    for (sector_position, mut current_sector_idx, mut material) in sectors_bottom.iter_mut() {
+    for (sector_position, mut material) in sectors_bottom.iter_mut() {
         if *sector_position == cursor_sector_position {
             *material = to_be_placed_sector_info.material.clone();
             if mouse_input.just_pressed(MouseButton::Left) {
-                *current_sector_idx = sector_idx;
-                event_writer.send(SectorPlacedEvent);
+                event_writer.send(PlayerActionEvent(PlayerAction::PlaceSector {
+                    backpack: sector_id.0 as usize,
+                    position: sector_position.0,
+                }));
             }
             break;
         }
@@ -624,13 +718,14 @@ fn sector_update_not_selected(
 
 fn sector_spawn_things(
     time: Res<Time>,
-    chests: Res<Chests>,
     enemies: Res<Enemies>,
     sectors: Res<Sectors>,
     chest_resources: Res<ChestResources>,
     hp_bar_resources: Res<HpBarResources>,
     player_progess: Res<PlayerProgress>,
+    enemy_hardness: Res<EnemyHardness>,
     player: Query<&Transform, With<Player>>,
+    mut run_rng: ResMut<RunRng>,
     mut commands: Commands,
     mut s: Query<(
         Entity,
@@ -654,106 +749,109 @@ fn sector_spawn_things(
             continue;
         }
 
-        if timer.0.finished() {
-            if let Some(empty_slot_position) = slots.0.iter().position(|slot| slot.is_none()) {
-                let angle = sector_id_to_start_angle(id.0) + SECTOR_ANGLE / 2.0
-                    - SECTOR_THING_GAP / 2.0 * (SECTOR_THINGS - 1) as f32
-                    + SECTOR_THING_GAP * empty_slot_position as f32;
-
-                let sector_info = &sectors.0[sector_idx.0];
-                let mut thread_rng = rand::thread_rng();
-
-                macro_rules! spawn_enemy {
-                    ( $x:expr ) => {
-                        slots.0[empty_slot_position] = Some(SlotType::Enemy);
-
-                        let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 30.0, Z_ENEMY)
-                            .with_scale(Vec3::new(2.0, 2.0, 2.0));
-                        t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
-
-                        let hardness = match player_progess.cycles {
-                            0 => 1.0,
-                            1 => 1.2,
-                            2 => 1.3,
-                            3 => 1.4,
-                            4 => 1.5,
-                            5 => 1.6,
-                            6 => 1.7,
-                            7 => 1.8,
-                            _ => 1.0,
-                        };
-
-                        spawn_enemy(
-                            &mut commands,
-                            hardness,
-                            enemies.as_ref(),
-                            $x,
-                            *id,
-                            hp_bar_resources.as_ref(),
-                            t,
-                            false,
-                        )
-                        .insert(SectorSlotEntity {
-                            entity,
-                            slot_position: empty_slot_position,
-                        });
-                    };
-                }
-
-                macro_rules! spawn_chest {
-                    ( $x:expr ) => {
-                        slots.0[empty_slot_position] = Some(SlotType::Item);
-
-                        let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 15.0, Z_CHEST);
-                        t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
-
-                        spawn_chest(&mut commands, chest_resources.as_ref(), $x, *id, t).insert(
-                            SectorSlotEntity {
-                                entity,
-                                slot_position: empty_slot_position,
-                            },
-                        );
-                    };
-                }
-
-                let random_enemy = if !sector_info.enemies.is_empty() {
-                    let random_enemy_idx =
-                        sector_info.enemies[thread_rng.gen_range(0..sector_info.enemies.len())];
-                    let enemy_info = &enemies[random_enemy_idx];
-                    if thread_rng.gen_bool(enemy_info.spawn_rate as f64) {
-                        Some(random_enemy_idx)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-                let random_chest = if !sector_info.chests.is_empty() {
-                    let random_chest_idx =
-                        sector_info.chests[thread_rng.gen_range(0..sector_info.chests.len())];
-                    let chest_info = &chests[random_chest_idx];
-
-                    if thread_rng.gen_bool(chest_info.spawn_rate as f64) {
-                        Some(random_chest_idx)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
+        if !timer.0.finished() || slots.0.iter().all(|slot| slot.is_some()) {
+            continue;
+        }
 
-                if random_enemy.is_some() && random_chest.is_some() {
-                    if thread_rng.gen_bool(0.5) {
-                        spawn_enemy!(random_enemy.unwrap());
-                    } else {
-                        spawn_chest!(random_chest.unwrap());
-                    }
-                } else if let Some(random_enemy_idx) = random_enemy {
-                    spawn_enemy!(random_enemy_idx);
-                } else if let Some(random_chest_idx) = random_chest {
-                    spawn_chest!(random_chest_idx);
-                }
-            }
+        let sector_info = &sectors[*sector_idx];
+        for spawn in sector_info.spawns.roll(&mut run_rng.spawn) {
+            let Some(empty_slot_position) = slots.0.iter().position(|slot| slot.is_none()) else {
+                break;
+            };
+            spawn_sector_thing(
+                &mut commands,
+                enemies.as_ref(),
+                chest_resources.as_ref(),
+                hp_bar_resources.as_ref(),
+                enemy_hardness.get(player_progess.cycles),
+                (entity, *id),
+                &mut slots,
+                empty_slot_position,
+                spawn,
+            );
         }
     }
 }
+
+/// Spawns an enemy or a chest in a slot of the sector and takes the slot.
+pub fn spawn_sector_thing<'a>(
+    commands: &'a mut Commands,
+    enemies: &Enemies,
+    chest_resources: &ChestResources,
+    hp_bar_resources: &HpBarResources,
+    hardness: f32,
+    (sector_entity, sector_position): (Entity, SectorPosition),
+    slots: &mut SectorSlots,
+    slot_position: usize,
+    spawn: SectorSpawn,
+) -> EntityCommands<'a> {
+    let angle = sector_id_to_start_angle(sector_position.0) + SECTOR_ANGLE / 2.0
+        - SECTOR_THING_GAP / 2.0 * (SECTOR_THINGS - 1) as f32
+        + SECTOR_THING_GAP * slot_position as f32;
+    let slot_entity = SectorSlotEntity {
+        entity: sector_entity,
+        slot_position,
+    };
+
+    match spawn {
+        SectorSpawn::Enemy(enemy_idx) => {
+            slots.0[slot_position] = Some(SlotType::Enemy);
+
+            let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 30.0, Z_ENEMY)
+                .with_scale(Vec3::new(2.0, 2.0, 2.0));
+            t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
+
+            let mut c = spawn_enemy(
+                commands,
+                hardness,
+                enemies,
+                enemy_idx,
+                sector_position,
+                hp_bar_resources,
+                t,
+                false,
+            );
+            c.insert(slot_entity);
+            c
+        }
+        SectorSpawn::Chest(chest_idx) => {
+            slots.0[slot_position] = Some(SlotType::Item);
+
+            let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 15.0, Z_CHEST);
+            t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
+
+            let mut c = spawn_chest(commands, chest_resources, chest_idx, sector_position, t);
+            c.insert(slot_entity);
+            c
+        }
+    }
+}
+
+/// Spawns the boss in the last sector. The run is won once it is gone.
+pub fn spawn_last_boss<'a>(
+    commands: &'a mut Commands,
+    enemies: &Enemies,
+    boss_idx: EnemyIdx,
+    hp_bar_resources: &HpBarResources,
+) -> EntityCommands<'a> {
+    let angle = sector_id_to_start_angle(SECTORS_NUM - 1) + SECTOR_ANGLE / 2.0
+        - SECTOR_THING_GAP / 2.0 * (SECTOR_THINGS - 1) as f32
+        + SECTOR_THING_GAP * 3.0;
+
+    let mut t = Transform::from_xyz(0.0, CIRCLE_RADIUS + 35.0, Z_ENEMY)
+        .with_scale(Vec3::new(2.5, 2.5, 2.5));
+    t.rotate_around(Vec3::ZERO, Quat::from_rotation_z(-angle));
+
+    let mut c = spawn_enemy(
+        commands,
+        1.0,
+        enemies,
+        boss_idx,
+        SectorPosition(SECTORS_NUM - 1),
+        hp_bar_resources,
+        t,
+        true,
+    );
+    c.insert(LastBossTag);
+    c
+}
@@ -0,0 +1,301 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::HashMap,
+};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::GlobalState;
+
+use super::{
+    chest::{ChestIdx, ChestsAsset},
+    circle_sectors::{SectorIdx, SectorsAsset},
+    enemy::{EnemiesAsset, EnemyIdx},
+    items::{ItemIdx, ItemsAsset},
+    spells::{SpellIdx, SpellsAsset},
+};
+
+pub struct ContentPlugin;
+
+impl Plugin for ContentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ContentLoadedEvent>()
+            .init_resource::<ContentIds>()
+            .add_systems(Update, build_content_ids);
+    }
+}
+
+/// Sent once all content catalogs are loaded and `ContentIds` is up to date,
+/// and again every time a catalog is hot reloaded.
+/// Catalog resources are rebuilt from their assets on this event.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentLoadedEvent;
+
+/// Handle to the data file of a content catalog.
+#[derive(Resource, Debug)]
+pub struct ContentHandle<A: Asset>(pub Handle<A>);
+
+#[derive(SystemParam)]
+pub struct ContentAsset<'w, 's, A: Asset> {
+    handle: Res<'w, ContentHandle<A>>,
+    assets: Res<'w, Assets<A>>,
+    events: EventReader<'w, 's, AssetEvent<A>>,
+}
+
+impl<A: Asset> ContentAsset<'_, '_, A> {
+    pub fn get(&self) -> Option<&A> {
+        self.assets.get(&self.handle.0)
+    }
+
+    /// Returns true if the catalog finished loading or was modified
+    /// since the last call.
+    fn loaded(&mut self) -> bool {
+        let mut loaded = false;
+        for e in self.events.read() {
+            loaded |=
+                e.is_loaded_with_dependencies(&self.handle.0) || e.is_modified(&self.handle.0);
+        }
+        loaded
+    }
+}
+
+/// Maps stable string ids of one catalog to positions in it.
+#[derive(Debug, Clone)]
+pub struct IdMap<I> {
+    kind: &'static str,
+    ids: HashMap<String, I>,
+}
+
+impl<I> Default for IdMap<I> {
+    fn default() -> Self {
+        Self {
+            kind: "",
+            ids: HashMap::default(),
+        }
+    }
+}
+
+impl<I: Copy> IdMap<I> {
+    pub fn new<'a>(
+        kind: &'static str,
+        ids: impl Iterator<Item = &'a str>,
+        to_idx: fn(usize) -> I,
+    ) -> Self {
+        let mut map = HashMap::default();
+        for (i, id) in ids.enumerate() {
+            if map.insert(id.to_string(), to_idx(i)).is_some() {
+                error!("duplicate {kind} id '{id}'");
+            }
+        }
+        Self { kind, ids: map }
+    }
+
+    pub fn get(&self, id: &str) -> Option<I> {
+        self.ids.get(id).copied()
+    }
+
+    /// Reverse of [`IdMap::get`].
+    pub fn id(&self, idx: I) -> Option<&str>
+    where
+        I: PartialEq,
+    {
+        self.ids
+            .iter()
+            .find(|(_, i)| **i == idx)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Returns true if every id of `self` has the same position in `other`.
+    fn kept_by(&self, other: &Self) -> bool
+    where
+        I: PartialEq,
+    {
+        self.ids.iter().all(|(id, idx)| other.get(id) == Some(*idx))
+    }
+
+    /// Resolves an id referenced by `owner`. Unknown ids are reported.
+    pub fn resolve(&self, owner: &str, id: &str) -> Option<I> {
+        let idx = self.get(id);
+        if idx.is_none() {
+            error!("{owner} references unknown {} id '{id}'", self.kind);
+        }
+        idx
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ContentIds {
+    pub items: IdMap<ItemIdx>,
+    pub spells: IdMap<SpellIdx>,
+    pub enemies: IdMap<EnemyIdx>,
+    pub chests: IdMap<ChestIdx>,
+    pub sectors: IdMap<SectorIdx>,
+}
+
+impl ContentIds {
+    /// Spawned entities and the inventory hold positions in the catalogs,
+    /// so a reload may only edit entries in place or add new ones at the end.
+    fn kept_by(&self, other: &Self) -> bool {
+        self.items.kept_by(&other.items)
+            && self.spells.kept_by(&other.spells)
+            && self.enemies.kept_by(&other.enemies)
+            && self.chests.kept_by(&other.chests)
+            && self.sectors.kept_by(&other.sectors)
+    }
+}
+
+/// Checks of a catalog for values that parse but would break the game.
+pub trait CheckContent {
+    /// Problems found, each one naming the entry it is in.
+    fn problems(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// Loads content catalogs (items, spells, ...) stored as `.ron` files.
+/// Each catalog uses its own double extension (e.g. `items.ron`),
+/// so several loaders can coexist.
+pub struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RonAssetLoaderError {
+    #[error("could not read content file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse content file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid content: {}", .0.join(", "))]
+    Invalid(Vec<String>),
+}
+
+impl<A> AssetLoader for RonAssetLoader<A>
+where
+    A: Asset + DeserializeOwned + CheckContent,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let asset: A = ron::de::from_bytes(&bytes)?;
+        let problems = asset.problems();
+        if !problems.is_empty() {
+            return Err(RonAssetLoaderError::Invalid(problems));
+        }
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+/// (De)serializes a [`Color`] as an `(r, g, b)` tuple of sRGB bytes.
+pub mod srgb_u8 {
+    use bevy::prelude::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b] = color.to_srgba().to_u8_array_no_alpha();
+        (r, g, b).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let (r, g, b) = <(u8, u8, u8)>::deserialize(deserializer)?;
+        Ok(Color::srgb_u8(r, g, b))
+    }
+}
+
+pub fn build_content_ids(
+    global_state: Res<State<GlobalState>>,
+    mut items: ContentAsset<ItemsAsset>,
+    mut spells: ContentAsset<SpellsAsset>,
+    mut enemies: ContentAsset<EnemiesAsset>,
+    mut chests: ContentAsset<ChestsAsset>,
+    mut sectors: ContentAsset<SectorsAsset>,
+    mut content_ids: ResMut<ContentIds>,
+    mut global_state_next: ResMut<NextState<GlobalState>>,
+    mut event_writer: EventWriter<ContentLoadedEvent>,
+) {
+    // Read every catalog, so no stale events are left for the next frame.
+    let loaded = [
+        items.loaded(),
+        spells.loaded(),
+        enemies.loaded(),
+        chests.loaded(),
+        sectors.loaded(),
+    ];
+    if !loaded.contains(&true) {
+        return;
+    }
+
+    // Catalogs reference each other, so wait for all of them.
+    let (Some(items), Some(spells), Some(enemies), Some(chests), Some(sectors)) = (
+        items.get(),
+        spells.get(),
+        enemies.get(),
+        chests.get(),
+        sectors.get(),
+    ) else {
+        return;
+    };
+
+    let new_content_ids = ContentIds {
+        items: IdMap::new("item", items.items.iter().map(|i| i.id.as_str()), ItemIdx),
+        spells: IdMap::new(
+            "spell",
+            spells.spells.iter().map(|s| s.id.as_str()),
+            SpellIdx,
+        ),
+        enemies: IdMap::new(
+            "enemy",
+            enemies.enemies.iter().map(|e| e.id.as_str()),
+            EnemyIdx,
+        ),
+        chests: IdMap::new(
+            "chest",
+            chests.chests.iter().map(|c| c.id.as_str()),
+            ChestIdx,
+        ),
+        sectors: IdMap::new(
+            "sector",
+            sectors.sectors.iter().map(|s| s.id.as_str()),
+            SectorIdx,
+        ),
+    };
+    if !content_ids.kept_by(&new_content_ids) {
+        error!("content reload moves or removes existing entries, restart the game to apply it");
+        return;
+    }
+    *content_ids = new_content_ids;
+    event_writer.send(ContentLoadedEvent);
+
+    if global_state.get() == &GlobalState::Loading {
+        info!("content loaded");
+        global_state_next.set(GlobalState::MainMenu);
+    } else {
+        info!("content reloaded");
+    }
+}
@@ -1,37 +1,72 @@
 use std::ops::{Index, IndexMut};
 
-use bevy::{audio::PlaybackMode, ecs::system::EntityCommands, prelude::*};
-use rand::Rng;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use serde::Deserialize;
 
 use crate::{ui::UiStyle, GlobalState};
 
 use super::{
-    animation::{spawn_damage_text, AllAnimations, AnimationConfig, AnimationFinishedEvent},
-    circle_sectors::{PlayerProgress, SectorIdx, SectorPosition, Sectors},
-    hp_bar::{hp_bar_bundle, HpBarResources},
-    inventory::{Inventory, InventoryUpdateEvent},
-    items::{ItemIdx, Items},
+    animation::{
+        spawn_damage_text, AllAnimations, AnimationConfig, AnimationFinishedEvent,
+        DAMAGE_COLOR_DEFAULT, DAMAGE_COLOR_SHIELD,
+    },
+    circle_sectors::{PlayerProgress, SectorPosition},
+    data::{
+        build_content_ids, CheckContent, ContentAsset, ContentHandle, ContentIds,
+        ContentLoadedEvent, RonAssetLoader,
+    },
+    hp_bar::{hp_bar_bundle, shield_bar_bundle, HpBarResources},
+    inventory::{Inventory, InventoryUpdateEvent, LootCollectedEvent},
+    loot::{resolve_rewards, LootTable, Reward, RewardId},
     player::DamagePlayerEvent,
-    sound::SoundResources,
-    spells::{SpellIdx, Spells},
-    AttackSpeed, Damage, Defense, GameState, Health,
+    rng::RunRng,
+    sound::{SoundCue, SoundCueEvent},
+    spells::{EnemyCastSpellEvent, SpellIdx},
+    stats::{DamageType, Resistances, Stats},
+    status_effects::{
+        apply_status_effects, DiminishingReturns, StatusEffectAppliedEvent, StatusEffectKind,
+        StatusEffects,
+    },
+    AttackLandedEvent, AttackSpeed, Crit, Damage, DamageTakenEvent, Defense, GameState,
+    GameplaySet, Health, Shield,
 };
 
+/// Enemy catalog, relative to the assets folder.
+pub const ENEMIES_DATA: &str = "data/enemies.ron";
+
+/// Id of the enemy spawned at the end of the last cycle.
+pub const BOSS_ENEMY_ID: &str = "boss";
+
+/// Tint of a stunned enemy.
+const STUNNED_COLOR: Color = Color::srgb(0.5, 0.6, 1.0);
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DamageEnemyEvent>()
             .add_event::<EnemyDeadEvent>()
+            .init_asset::<EnemiesAsset>()
+            .register_asset_loader(RonAssetLoader::<EnemiesAsset>::new(&["enemies.ron"]))
             .add_systems(Startup, prepare_enemy_resources)
+            .add_systems(Update, build_enemies.after(build_content_ids))
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
-                    enemy_attack,
-                    on_attack_finish,
-                    enemy_take_damage,
-                    on_dead_finish,
-                    enemy_check_dead,
+                    (
+                        enemy_crowd_control,
+                        enemy_attack,
+                        enemy_cast_spells,
+                        show_stunned,
+                    )
+                        .chain()
+                        .after(apply_status_effects)
+                        .in_set(GameplaySet::Tick),
+                    on_attack_finish.in_set(GameplaySet::Attack),
+                    enemy_take_damage.in_set(GameplaySet::Damage),
+                    (enemy_check_dead, on_dead_finish)
+                        .chain()
+                        .in_set(GameplaySet::Check),
                 )
                     .run_if(in_state(GameState::Battle)),
             );
@@ -42,6 +77,8 @@ impl Plugin for EnemyPlugin {
 pub struct DamageEnemyEvent {
     pub damage: f32,
     pub color: Color,
+    pub damage_type: DamageType,
+    pub crit: bool,
 }
 
 #[derive(Event, Debug, Clone, PartialEq)]
@@ -61,6 +98,124 @@ pub struct BattleEnemy;
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BattleEnemyDead;
 
+/// The enemy sprite is tinted with `STUNNED_COLOR`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ShownStunned;
+
+/// One animation of an enemy: a sprite sheet and the frames to play from it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemySheetData {
+    pub texture: String,
+    pub first: usize,
+    pub last: usize,
+    pub fps: u8,
+}
+
+impl EnemySheetData {
+    fn check(&self, owner: &str, frames: usize, problems: &mut Vec<String>) {
+        if self.fps == 0 {
+            problems.push(format!("{owner} plays '{}' at 0 fps", self.texture));
+        }
+        if self.last < self.first || frames <= self.last {
+            problems.push(format!(
+                "{owner} uses frames {}..={} of '{}', but its grid has {frames}",
+                self.first, self.last, self.texture
+            ));
+        }
+    }
+}
+
+/// Layout shared by all sheets of an enemy.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct EnemyGridData {
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// When an enemy casts a spell, once its cooldown is over.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+pub enum CastCondition {
+    #[default]
+    Always,
+    /// While its health is below this part of the max.
+    HealthBelow(f32),
+    /// After this many of its attacks since the last cast.
+    EveryAttacks(u32),
+}
+
+/// Spell of an enemy, referenced by its id in the spell catalog.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemySpellData {
+    pub spell: String,
+    /// Cooldown in seconds, kept per enemy.
+    pub cooldown: f32,
+    #[serde(default)]
+    pub when: CastCondition,
+}
+
+/// Single entry of the `assets/data/enemies.ron` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyData {
+    pub id: String,
+    pub idle: EnemySheetData,
+    pub attack: EnemySheetData,
+    pub dead: EnemySheetData,
+    pub grid: EnemyGridData,
+    #[serde(default)]
+    pub loot: LootTable<RewardId>,
+    pub hp: f32,
+    pub damage: f32,
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    #[serde(default)]
+    pub spells: Vec<EnemySpellData>,
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct EnemiesAsset {
+    pub enemies: Vec<EnemyData>,
+}
+
+impl CheckContent for EnemiesAsset {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for enemy_data in self.enemies.iter() {
+            let id = &enemy_data.id;
+            let frames = (enemy_data.grid.columns * enemy_data.grid.rows) as usize;
+            for sheet in [&enemy_data.idle, &enemy_data.attack, &enemy_data.dead] {
+                sheet.check(id, frames, &mut problems);
+            }
+            enemy_data.loot.check(id, &mut problems);
+            for enemy_spell in enemy_data.spells.iter() {
+                let spell = &enemy_spell.spell;
+                if !(0.0..f32::INFINITY).contains(&enemy_spell.cooldown) {
+                    problems.push(format!(
+                        "{id} has invalid cooldown {} for '{spell}'",
+                        enemy_spell.cooldown
+                    ));
+                }
+                match enemy_spell.when {
+                    CastCondition::Always | CastCondition::EveryAttacks(1..) => {}
+                    CastCondition::HealthBelow(part) => {
+                        if !(0.0..=1.0).contains(&part) {
+                            problems.push(format!(
+                                "{id} casts '{spell}' below health part {part} outside of [0, 1]"
+                            ));
+                        }
+                    }
+                    CastCondition::EveryAttacks(0) => {
+                        problems.push(format!("{id} casts '{spell}' every 0 attacks"));
+                    }
+                }
+            }
+        }
+        problems
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EnemyInfo {
     pub idle_texture: Handle<Image>,
@@ -74,15 +229,34 @@ pub struct EnemyInfo {
 
     pub texture_atlas: TextureAtlas,
 
-    pub spawn_rate: f32,
-    pub items: Vec<ItemIdx>,
-    pub spells: Vec<SpellIdx>,
-    pub sectors: Vec<SectorIdx>,
+    pub loot: LootTable<Reward>,
 
     pub hp: f32,
     pub damage: f32,
+    pub crit_chance: f32,
+    pub resistances: Resistances,
+    pub spells: Vec<EnemySpellInfo>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnemySpellInfo {
+    pub spell: SpellIdx,
+    pub cooldown: f32,
+    pub when: CastCondition,
 }
 
+#[derive(Debug, Clone)]
+struct EnemySpell {
+    spell: SpellIdx,
+    cooldown: Timer,
+    when: CastCondition,
+    attacks: u32,
+}
+
+/// Spells of a spawned enemy with their own cooldowns.
+#[derive(Component, Debug, Clone)]
+pub struct EnemySpells(Vec<EnemySpell>);
+
 #[derive(Resource, Debug, Clone)]
 pub struct Enemies(Vec<EnemyInfo>);
 
@@ -99,194 +273,102 @@ impl IndexMut<EnemyIdx> for Enemies {
     }
 }
 
-fn prepare_enemy_resources(
+fn prepare_enemy_resources(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(ContentHandle::<EnemiesAsset>(
+        asset_server.load(ENEMIES_DATA),
+    ));
+    // Filled by `build_enemies` once the data file is loaded.
+    commands.insert_resource(Enemies(vec![]));
+}
+
+fn build_enemies(
     asset_server: Res<AssetServer>,
-    mut commands: Commands,
+    content_ids: Res<ContentIds>,
+    enemies_asset: ContentAsset<EnemiesAsset>,
+    mut enemies: ResMut<Enemies>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut event_reader: EventReader<ContentLoadedEvent>,
 ) {
-    let mut enemies = Enemies(vec![]);
-
-    // Big boss
-    let idle_texture = asset_server.load("enemy/boss_idle_sheet.png");
-    let idle_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossIdle, false, true);
-
-    let attack_texture = asset_server.load("enemy/boss_attack_sheet.png");
-    let attack_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossAttack, true, false);
-
-    let dead_texture = asset_server.load("enemy/boss_dead_sheet.png");
-    let dead_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossDead, true, false);
-
-    let texture_layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 1, None, None);
-    let atlas_handle = texture_atlas_layouts.add(texture_layout);
-    let texture_atlas = TextureAtlas {
-        layout: atlas_handle,
-        index: 1,
-    };
-    enemies.0.push(EnemyInfo {
-        idle_texture,
-        idle_animation_config,
-
-        attack_texture,
-        attack_animation_config,
-
-        dead_texture,
-        dead_animation_config,
-
-        texture_atlas,
-
-        spawn_rate: 0.3,
-        items: vec![ItemIdx(0), ItemIdx(1), ItemIdx(2)],
-        spells: vec![
-            SpellIdx(0),
-            SpellIdx(1),
-            SpellIdx(2),
-            SpellIdx(3),
-            SpellIdx(4),
-        ],
-        sectors: vec![SectorIdx(0)],
-
-        hp: 700.0,
-        damage: 20.0,
-    });
-
-    // Green
-    let idle_texture = asset_server.load("enemy/greenmob_idle_sheet.png");
-    let idle_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossIdle, false, true);
-
-    let attack_texture = asset_server.load("enemy/greenmob_attack_sheet.png");
-    let attack_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossAttack, true, false);
-
-    let dead_texture = asset_server.load("enemy/greenmob_dead_sheet.png");
-    let dead_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossDead, true, false);
-
-    let texture_layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 1, None, None);
-    let atlas_handle = texture_atlas_layouts.add(texture_layout);
-    let texture_atlas = TextureAtlas {
-        layout: atlas_handle,
-        index: 1,
-    };
-    // 1
-    enemies.0.push(EnemyInfo {
-        idle_texture: idle_texture.clone(),
-        idle_animation_config: idle_animation_config.clone(),
-
-        attack_texture: attack_texture.clone(),
-        attack_animation_config: attack_animation_config.clone(),
-
-        dead_texture: dead_texture.clone(),
-        dead_animation_config: dead_animation_config.clone(),
-
-        texture_atlas: texture_atlas.clone(),
-
-        spawn_rate: 0.3,
-
-        // Plant, Stickynotes
-        items: vec![ItemIdx(2), ItemIdx(5)],
-        // Marker, Lunchbox
-        spells: vec![SpellIdx(0), SpellIdx(3)],
-        sectors: vec![SectorIdx(1), SectorIdx(2)],
-
-        hp: 50.0,
-        damage: 3.0,
-    });
-
-    // 2
-    enemies.0.push(EnemyInfo {
-        idle_texture,
-        idle_animation_config,
-
-        attack_texture,
-        attack_animation_config,
-
-        dead_texture,
-        dead_animation_config,
-
-        texture_atlas,
-
-        spawn_rate: 0.3,
-
-        // Coffee, Plant, Stickynotes
-        items: vec![ItemIdx(0), ItemIdx(2), ItemIdx(5)],
-        // Marker, Keyboard
-        spells: vec![SpellIdx(0), SpellIdx(1)],
-        sectors: vec![SectorIdx(3), SectorIdx(4)],
-
-        hp: 75.0,
-        damage: 5.0,
-    });
-
-    // Orange
-    let idle_texture = asset_server.load("enemy/orangemob_idle_sheet.png");
-    let idle_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossIdle, false, true);
-
-    let attack_texture = asset_server.load("enemy/orangemob_attack_sheet.png");
-    let attack_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossAttack, true, false);
-
-    let dead_texture = asset_server.load("enemy/orangemob_dead_sheet.png");
-    let dead_animation_config =
-        AnimationConfig::new(0, 5, 10, AllAnimations::BossDead, true, false);
-
-    let texture_layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 6, 1, None, None);
-    let atlas_handle = texture_atlas_layouts.add(texture_layout);
-    let texture_atlas = TextureAtlas {
-        layout: atlas_handle,
-        index: 1,
-    };
-    // 3
-    enemies.0.push(EnemyInfo {
-        idle_texture: idle_texture.clone(),
-        idle_animation_config: idle_animation_config.clone(),
-
-        attack_texture: attack_texture.clone(),
-        attack_animation_config: attack_animation_config.clone(),
-
-        dead_texture: dead_texture.clone(),
-        dead_animation_config: dead_animation_config.clone(),
-
-        texture_atlas: texture_atlas.clone(),
-
-        spawn_rate: 0.3,
-        // Paperclip, Scissors, Stickynotes
-        items: vec![ItemIdx(1), ItemIdx(3), ItemIdx(5)],
-        spells: vec![],
-        sectors: vec![SectorIdx(2), SectorIdx(3)],
-
-        hp: 120.0,
-        damage: 8.0,
-    });
-
-    // 4
-    enemies.0.push(EnemyInfo {
-        idle_texture,
-        idle_animation_config,
-
-        attack_texture,
-        attack_animation_config,
-
-        dead_texture,
-        dead_animation_config,
-
-        texture_atlas,
-
-        spawn_rate: 0.3,
-        // Plant, Stapler
-        items: vec![ItemIdx(2), ItemIdx(4)],
-        spells: vec![SpellIdx(2)],
-        sectors: vec![SectorIdx(4)],
-
-        hp: 140.0,
-        damage: 10.0,
-    });
-
-    commands.insert_resource(enemies);
+    for _ in event_reader.read() {
+        let Some(enemies_asset) = enemies_asset.get() else {
+            continue;
+        };
+
+        enemies.0 = enemies_asset
+            .enemies
+            .iter()
+            .map(|enemy_data| {
+                let sheet = |data: &EnemySheetData,
+                             animation: AllAnimations,
+                             send_finish_event: bool,
+                             continues: bool| {
+                    (
+                        asset_server.load(data.texture.clone()),
+                        AnimationConfig::new(
+                            data.first,
+                            data.last,
+                            data.fps,
+                            animation,
+                            send_finish_event,
+                            continues,
+                        ),
+                    )
+                };
+                let (idle_texture, idle_animation_config) =
+                    sheet(&enemy_data.idle, AllAnimations::BossIdle, false, true);
+                let (attack_texture, attack_animation_config) =
+                    sheet(&enemy_data.attack, AllAnimations::BossAttack, true, false);
+                let (dead_texture, dead_animation_config) =
+                    sheet(&enemy_data.dead, AllAnimations::BossDead, true, false);
+
+                let grid = enemy_data.grid;
+                let texture_layout = TextureAtlasLayout::from_grid(
+                    UVec2::new(grid.tile_size.0, grid.tile_size.1),
+                    grid.columns,
+                    grid.rows,
+                    None,
+                    None,
+                );
+                let texture_atlas = TextureAtlas {
+                    layout: texture_atlas_layouts.add(texture_layout),
+                    index: idle_animation_config.first_sprite_index,
+                };
+
+                EnemyInfo {
+                    idle_texture,
+                    idle_animation_config,
+
+                    attack_texture,
+                    attack_animation_config,
+
+                    dead_texture,
+                    dead_animation_config,
+
+                    texture_atlas,
+
+                    loot: resolve_rewards(&content_ids, &enemy_data.id, &enemy_data.loot),
+
+                    hp: enemy_data.hp,
+                    damage: enemy_data.damage,
+                    crit_chance: enemy_data.crit_chance,
+                    resistances: enemy_data.resistances.clone(),
+                    spells: enemy_data
+                        .spells
+                        .iter()
+                        .filter_map(|spell_data| {
+                            Some(EnemySpellInfo {
+                                spell: content_ids
+                                    .spells
+                                    .resolve(&enemy_data.id, &spell_data.spell)?,
+                                cooldown: spell_data.cooldown,
+                                when: spell_data.when,
+                            })
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+    }
 }
 
 pub fn spawn_enemy<'a>(
@@ -313,22 +395,81 @@ pub fn spawn_enemy<'a>(
         Damage(enemy_info.damage * hardness),
         AttackSpeed::new(1.0),
         Defense(0.0),
+        Crit::new(enemy_info.crit_chance),
+        Stats::default(),
+        Shield::default(),
+        EnemySpells(
+            enemy_info
+                .spells
+                .iter()
+                .map(|spell_info| EnemySpell {
+                    spell: spell_info.spell,
+                    cooldown: Timer::from_seconds(spell_info.cooldown, TimerMode::Once),
+                    when: spell_info.when,
+                    attacks: 0,
+                })
+                .collect(),
+        ),
         sector_id,
         enemy_idx,
         StateScoped(GlobalState::InGame),
     ));
+    if is_boss {
+        c.insert(DiminishingReturns::default());
+    }
     let parent_entity = c.id();
     c.with_children(|builder| {
         builder.spawn(hp_bar_bundle(hp_bar_resources, parent_entity));
+        builder.spawn(shield_bar_bundle(hp_bar_resources, parent_entity));
     });
     c
 }
 
+/// Cancels the attack in progress of a stunned or interrupted enemy.
+fn enemy_crowd_control(
+    enemies: Res<Enemies>,
+    mut enemy: Query<
+        (
+            Entity,
+            &EnemyIdx,
+            &mut AttackSpeed,
+            &mut AnimationConfig,
+            &mut Handle<Image>,
+            &mut TextureAtlas,
+        ),
+        With<BattleEnemy>,
+    >,
+    mut event_reader: EventReader<StatusEffectAppliedEvent>,
+) {
+    let Ok((enemy_entity, enemy_idx, mut attack_speed, mut config, mut texture, mut atlas)) =
+        enemy.get_single_mut()
+    else {
+        return;
+    };
+
+    for e in event_reader.read() {
+        if e.target != enemy_entity || !e.kind.is_crowd_control() {
+            continue;
+        }
+        if e.kind == StatusEffectKind::Interrupt {
+            attack_speed.0.reset();
+        }
+        if config.animation == AllAnimations::BossAttack {
+            let enemy_info = &enemies[*enemy_idx];
+            *texture = enemy_info.idle_texture.clone();
+            atlas.index = enemy_info.idle_animation_config.first_sprite_index;
+            *config = enemy_info.idle_animation_config.clone();
+        }
+    }
+}
+
 fn enemy_attack(
     time: Res<Time>,
     enemies: Res<Enemies>,
+    status_effects: StatusEffects,
     mut enemy: Query<
         (
+            Entity,
             &EnemyIdx,
             &mut AttackSpeed,
             &mut AnimationConfig,
@@ -338,12 +479,16 @@ fn enemy_attack(
         With<BattleEnemy>,
     >,
 ) {
-    let Ok((enemy_idx, mut attack_speed, mut config, mut texture, mut atlas)) =
+    let Ok((enemy_entity, enemy_idx, mut attack_speed, mut config, mut texture, mut atlas)) =
         enemy.get_single_mut()
     else {
         return;
     };
 
+    if status_effects.is_stunned(enemy_entity) {
+        return;
+    }
+
     attack_speed.0.tick(time.delta());
     if attack_speed.0.finished() {
         let enemy_info = &enemies[*enemy_idx];
@@ -354,60 +499,169 @@ fn enemy_attack(
     }
 }
 
-fn on_attack_finish(
-    enemy: Query<(&Enemy, &Damage), With<BattleEnemy>>,
-    sounds: Res<SoundResources>,
+fn enemy_cast_spells(
+    time: Res<Time>,
+    status_effects: StatusEffects,
+    mut enemy: Query<(Entity, &Health, &mut EnemySpells), With<BattleEnemy>>,
+    mut attack_reader: EventReader<AttackLandedEvent>,
+    mut event_writer: EventWriter<EnemyCastSpellEvent>,
+) {
+    let Ok((enemy_entity, enemy_health, mut enemy_spells)) = enemy.get_single_mut() else {
+        return;
+    };
+
+    let attacks = attack_reader
+        .read()
+        .filter(|e| e.attacker == enemy_entity)
+        .count() as u32;
+    if status_effects.is_stunned(enemy_entity) {
+        return;
+    }
+
+    for enemy_spell in enemy_spells.0.iter_mut() {
+        enemy_spell.cooldown.tick(time.delta());
+        enemy_spell.attacks += attacks;
+        if !enemy_spell.cooldown.finished() {
+            continue;
+        }
+
+        let cast = match enemy_spell.when {
+            CastCondition::Always => true,
+            CastCondition::HealthBelow(part) => enemy_health.current() < enemy_health.max() * part,
+            CastCondition::EveryAttacks(attacks) => attacks <= enemy_spell.attacks,
+        };
+        if cast {
+            enemy_spell.cooldown.reset();
+            enemy_spell.attacks = 0;
+            event_writer.send(EnemyCastSpellEvent {
+                caster: enemy_entity,
+                spell: enemy_spell.spell,
+            });
+        }
+    }
+}
+
+/// Only touches the sprite when the stun starts or ends.
+fn show_stunned(
+    status_effects: StatusEffects,
     mut commands: Commands,
+    mut enemy: Query<(Entity, &mut Sprite, Has<ShownStunned>), With<BattleEnemy>>,
+) {
+    let Ok((enemy_entity, mut sprite, shown_stunned)) = enemy.get_single_mut() else {
+        return;
+    };
+
+    let stunned = status_effects.is_stunned(enemy_entity);
+    if stunned && !shown_stunned {
+        sprite.color = STUNNED_COLOR;
+        commands.entity(enemy_entity).insert(ShownStunned);
+    } else if !stunned && shown_stunned {
+        sprite.color = Color::WHITE;
+        commands.entity(enemy_entity).remove::<ShownStunned>();
+    }
+}
+
+fn on_attack_finish(
+    mut run_rng: ResMut<RunRng>,
+    enemy: Query<(Entity, &Enemy, &Stats), With<BattleEnemy>>,
     mut event_reader: EventReader<AnimationFinishedEvent>,
     mut event_writer: EventWriter<DamagePlayerEvent>,
+    mut attack_event: EventWriter<AttackLandedEvent>,
+    mut sound_event: EventWriter<SoundCueEvent>,
 ) {
-    let Ok((enemy, damage)) = enemy.get_single() else {
+    let Ok((enemy_entity, enemy, enemy_stats)) = enemy.get_single() else {
         return;
     };
 
     for e in event_reader.read() {
         if e.0 == AllAnimations::BossAttack {
-            // Attack sound
-            commands.spawn(AudioBundle {
-                source: if enemy.is_boss {
-                    sounds.boss_attack.clone()
-                } else {
-                    sounds.enemy_attack.clone()
-                },
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: sounds.volume,
-                    ..Default::default()
-                },
+            sound_event.send(SoundCueEvent(if enemy.is_boss {
+                SoundCue::BossAttack
+            } else {
+                SoundCue::EnemyAttack
+            }));
+
+            let (damage, crit) = enemy_stats.roll_crit(enemy_stats.damage(), &mut run_rng.combat);
+            event_writer.send(DamagePlayerEvent {
+                damage,
+                color: DAMAGE_COLOR_DEFAULT,
+                damage_type: DamageType::Physical,
+                crit,
+            });
+            attack_event.send(AttackLandedEvent {
+                attacker: enemy_entity,
+                damage,
             });
-
-            event_writer.send(DamagePlayerEvent(damage.0));
         }
     }
 }
 
 fn enemy_take_damage(
     ui_style: Res<UiStyle>,
+    enemies: Res<Enemies>,
     mut commands: Commands,
-    mut enemy: Query<(&Transform, &Defense, &mut Health), With<BattleEnemy>>,
+    mut enemy: Query<
+        (
+            Entity,
+            &Transform,
+            &EnemyIdx,
+            &Stats,
+            &mut Shield,
+            &mut Health,
+        ),
+        With<BattleEnemy>,
+    >,
     mut event_reader: EventReader<DamageEnemyEvent>,
+    mut damage_taken_event: EventWriter<DamageTakenEvent>,
 ) {
-    let Ok((enemy_transform, enemy_defense, mut enemy_health)) = enemy.get_single_mut() else {
+    let Ok((
+        enemy_entity,
+        enemy_transform,
+        enemy_idx,
+        enemy_stats,
+        mut enemy_shield,
+        mut enemy_health,
+    )) = enemy.get_single_mut()
+    else {
         return;
     };
+    let resistances = &enemies[*enemy_idx].resistances;
 
     for e in event_reader.read() {
-        let damage = e.damage * (1.0 - enemy_defense.0);
+        let damage =
+            e.damage * (1.0 - enemy_stats.defense()) * (1.0 - resistances.get(e.damage_type));
+        let absorbed = enemy_shield.absorb(damage);
+        let damage = damage - absorbed;
         enemy_health.take_damage(damage);
+        if 0.0 < damage {
+            damage_taken_event.send(DamageTakenEvent {
+                target: enemy_entity,
+                damage,
+            });
+        }
 
-        spawn_damage_text(
-            &mut commands,
-            ui_style.as_ref(),
-            damage,
-            *enemy_transform,
-            enemy_transform.translation.normalize(),
-            e.color,
-        );
+        if 0.0 < absorbed {
+            spawn_damage_text(
+                &mut commands,
+                ui_style.as_ref(),
+                absorbed,
+                *enemy_transform,
+                enemy_transform.translation.normalize(),
+                DAMAGE_COLOR_SHIELD,
+                e.crit,
+            );
+        }
+        if 0.0 < damage || absorbed == 0.0 {
+            spawn_damage_text(
+                &mut commands,
+                ui_style.as_ref(),
+                damage,
+                *enemy_transform,
+                enemy_transform.translation.normalize(),
+                e.color,
+                e.crit,
+            );
+        }
     }
 }
 
@@ -422,12 +676,22 @@ fn enemy_check_dead(
             &mut AnimationConfig,
             &mut Handle<Image>,
             &mut TextureAtlas,
+            &mut Sprite,
+            Has<ShownStunned>,
         ),
         With<BattleEnemy>,
     >,
 ) {
-    let Ok((enemy_entity, enemy_health, enemy_idx, mut config, mut texture, mut atlas)) =
-        enemy.get_single_mut()
+    let Ok((
+        enemy_entity,
+        enemy_health,
+        enemy_idx,
+        mut config,
+        mut texture,
+        mut atlas,
+        mut sprite,
+        shown_stunned,
+    )) = enemy.get_single_mut()
     else {
         return;
     };
@@ -436,9 +700,14 @@ fn enemy_check_dead(
         commands
             .get_entity(enemy_entity)
             .unwrap()
-            .remove::<BattleEnemy>()
+            .remove::<(BattleEnemy, ShownStunned)>()
             .insert(BattleEnemyDead);
 
+        // `show_stunned` stops with the battle enemy.
+        if shown_stunned {
+            sprite.color = Color::WHITE;
+        }
+
         let enemy_info = &enemies[*enemy_idx];
 
         // Start dead animation
@@ -449,15 +718,14 @@ fn enemy_check_dead(
 }
 
 fn on_dead_finish(
-    items: Res<Items>,
-    spells: Res<Spells>,
     enemies: Res<Enemies>,
-    sectors: Res<Sectors>,
     enemy: Query<(Entity, &EnemyIdx), With<BattleEnemyDead>>,
     mut commands: Commands,
     mut inventory: ResMut<Inventory>,
+    mut run_rng: ResMut<RunRng>,
     mut event_reader: EventReader<AnimationFinishedEvent>,
     mut inventory_update_event: EventWriter<InventoryUpdateEvent>,
+    mut loot_event: EventWriter<LootCollectedEvent>,
     mut enemy_dead_event: EventWriter<EnemyDeadEvent>,
 ) {
     let Ok((entity, enemy_idx)) = enemy.get_single() else {
@@ -469,34 +737,9 @@ fn on_dead_finish(
             commands.get_entity(entity).unwrap().despawn_recursive();
 
             let enemy_info = &enemies[*enemy_idx];
-
-            let mut thread_rng = rand::thread_rng();
-
-            if !enemy_info.items.is_empty() {
-                let random_item_idx =
-                    enemy_info.items[thread_rng.gen_range(0..enemy_info.items.len())];
-                let item = &items[random_item_idx];
-                if thread_rng.gen_bool(item.drop_rate as f64) {
-                    inventory.backpack_items.push(random_item_idx);
-                }
-            }
-
-            if !enemy_info.spells.is_empty() {
-                let random_spell_idx =
-                    enemy_info.spells[thread_rng.gen_range(0..enemy_info.spells.len())];
-                let spell = &spells[random_spell_idx];
-                if thread_rng.gen_bool(spell.drop_rate as f64) {
-                    inventory.backpack_spells.push(random_spell_idx);
-                }
-            }
-
-            if !enemy_info.sectors.is_empty() {
-                let random_sector_idx =
-                    enemy_info.sectors[thread_rng.gen_range(0..enemy_info.sectors.len())];
-                let sector = &sectors[random_sector_idx];
-                if thread_rng.gen_bool(sector.drop_rate as f64) {
-                    inventory.backpack_sectors.push(random_sector_idx);
-                }
+            for reward in enemy_info.loot.roll(&mut run_rng.loot) {
+                inventory.add_reward(reward);
+                loot_event.send(LootCollectedEvent(reward));
             }
 
             info!("enemy dead event");
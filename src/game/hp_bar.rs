@@ -1,6 +1,6 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
-use super::{GameState, Health};
+use super::{animation::DAMAGE_COLOR_SHIELD, GameState, Health, Shield};
 
 const HP_BAR_WIDTH: f32 = 10.0;
 const HP_BAR_HEIGHT: f32 = 2.0;
@@ -10,7 +10,14 @@ pub struct HpBarPlugin;
 impl Plugin for HpBarPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, prepare_hp_bar_resources)
-            .add_systems(Update, update_hp_bar.run_if(in_state(GameState::Battle)));
+            .add_systems(
+                Update,
+                (
+                    update_hp_bar.run_if(in_state(GameState::Battle)),
+                    // The shield is also cleared after the battle.
+                    update_shield_bar,
+                ),
+            );
     }
 }
 
@@ -18,6 +25,7 @@ impl Plugin for HpBarPlugin {
 pub struct HpBarResources {
     mesh: Handle<Mesh>,
     material: Handle<ColorMaterial>,
+    shield_material: Handle<ColorMaterial>,
 }
 
 #[derive(Component, Debug)]
@@ -25,6 +33,12 @@ pub struct HpBar {
     parent_entity: Entity,
 }
 
+/// Drawn above the `HpBar`, as a part of the max health.
+#[derive(Component, Debug)]
+pub struct ShieldBar {
+    parent_entity: Entity,
+}
+
 fn prepare_hp_bar_resources(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -32,7 +46,12 @@ fn prepare_hp_bar_resources(
 ) {
     let mesh = meshes.add(Rectangle::new(HP_BAR_WIDTH, HP_BAR_HEIGHT));
     let material = materials.add(Color::srgb(1.0, 0.0, 0.0));
-    commands.insert_resource(HpBarResources { mesh, material })
+    let shield_material = materials.add(DAMAGE_COLOR_SHIELD);
+    commands.insert_resource(HpBarResources {
+        mesh,
+        material,
+        shield_material,
+    })
 }
 
 pub fn hp_bar_bundle(hp_bar_resources: &HpBarResources, parent_entity: Entity) -> impl Bundle {
@@ -47,6 +66,19 @@ pub fn hp_bar_bundle(hp_bar_resources: &HpBarResources, parent_entity: Entity) -
     )
 }
 
+pub fn shield_bar_bundle(hp_bar_resources: &HpBarResources, parent_entity: Entity) -> impl Bundle {
+    (
+        MaterialMesh2dBundle {
+            mesh: hp_bar_resources.mesh.clone().into(),
+            material: hp_bar_resources.shield_material.clone(),
+            transform: Transform::from_xyz(0.0, 2.0 + HP_BAR_HEIGHT, 0.0)
+                .with_scale(Vec3::new(0.0, 1.0, 1.0)),
+            ..default()
+        },
+        ShieldBar { parent_entity },
+    )
+}
+
 fn update_hp_bar(with_hp: Query<&Health>, mut hp_bars: Query<(&HpBar, &mut Transform)>) {
     for (hp_bar, mut hp_bar_transform) in hp_bars.iter_mut() {
         let Ok(health) = with_hp.get(hp_bar.parent_entity) else {
@@ -59,3 +91,19 @@ fn update_hp_bar(with_hp: Query<&Health>, mut hp_bars: Query<(&HpBar, &mut Trans
         hp_bar_transform.translation.x = -offset;
     }
 }
+
+fn update_shield_bar(
+    with_shield: Query<(&Shield, &Health), Or<(Changed<Shield>, Changed<Health>)>>,
+    mut shield_bars: Query<(&ShieldBar, &mut Transform)>,
+) {
+    for (shield_bar, mut shield_bar_transform) in shield_bars.iter_mut() {
+        let Ok((shield, health)) = with_shield.get(shield_bar.parent_entity) else {
+            continue;
+        };
+        let percent = (shield.0 / health.max()).min(1.0);
+
+        shield_bar_transform.scale.x = percent;
+        let offset = HP_BAR_WIDTH / 2.0 * (1.0 - percent);
+        shield_bar_transform.translation.x = -offset;
+    }
+}
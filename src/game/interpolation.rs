@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, restore_fixed_transforms)
+            .add_systems(FixedLast, store_fixed_transforms)
+            .add_systems(Update, interpolate_transforms);
+    }
+}
+
+/// For entities moved in `FixedUpdate`. Their `Transform` is drawn between
+/// the last two fixed steps, while gameplay only ever sees the stepped one.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct InterpolatedTransform {
+    previous: Transform,
+    current: Transform,
+}
+
+impl InterpolatedTransform {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+        }
+    }
+}
+
+fn restore_fixed_transforms(mut query: Query<(&InterpolatedTransform, &mut Transform)>) {
+    for (interpolated, mut transform) in query.iter_mut() {
+        *transform = interpolated.current;
+    }
+}
+
+fn store_fixed_transforms(mut query: Query<(&mut InterpolatedTransform, &Transform)>) {
+    for (mut interpolated, transform) in query.iter_mut() {
+        interpolated.previous = interpolated.current;
+        interpolated.current = *transform;
+    }
+}
+
+pub fn interpolate_transforms(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&InterpolatedTransform, &mut Transform)>,
+) {
+    let t = time.overstep_fraction();
+    for (interpolated, mut transform) in query.iter_mut() {
+        let (previous, current) = (interpolated.previous, interpolated.current);
+        transform.translation = previous.translation.lerp(current.translation, t);
+        transform.rotation = previous.rotation.slerp(current.rotation, t);
+        transform.scale = previous.scale.lerp(current.scale, t);
+    }
+}
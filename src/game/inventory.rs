@@ -2,32 +2,29 @@ use std::ops::Index;
 
 use bevy::prelude::*;
 
-use crate::ui::in_game::{BackpackSectorId, SelectedSectionButton};
-
-use super::{
-    circle_sectors::{SectorIdx, SectorPlacedEvent},
-    items::ItemIdx,
-    spells::SpellIdx,
-    GameState,
-};
+use super::{circle_sectors::SectorIdx, items::ItemIdx, loot::Reward, spells::SpellIdx};
 
 pub struct InventoryPlugin;
 
-const INVENTORY_ITEMS: usize = 4;
-const INVENTORY_BACKPACK_ITEMS: usize = 4;
-const INVENTORY_BACKPACK_SECTORS: usize = 4;
+pub const INVENTORY_ITEMS: usize = 4;
+pub const INVENTORY_BACKPACK_ITEMS: usize = 4;
+pub const INVENTORY_BACKPACK_SECTORS: usize = 4;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<InventoryUpdateEvent>()
-            .add_systems(Startup, prepare_inventory)
-            .add_systems(Update, on_sector_placed.run_if(state_exists::<GameState>));
+            .add_event::<LootCollectedEvent>()
+            .add_systems(Startup, prepare_inventory);
     }
 }
 
 #[derive(Event, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InventoryUpdateEvent;
 
+/// Sent for every reward put into the backpack.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LootCollectedEvent(pub Reward);
+
 #[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Inventory {
     pub active_items: Stack<ItemIdx, INVENTORY_ITEMS>,
@@ -37,6 +34,12 @@ pub struct Inventory {
     pub backpack_sectors: Stack<SectorIdx, INVENTORY_BACKPACK_SECTORS>,
 }
 
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Inventory {
     pub fn new() -> Self {
         Self {
@@ -49,21 +52,29 @@ impl Inventory {
     }
 
     pub fn equip_item(&mut self, id: usize) {
-        if let Some(item_idx) = self.backpack_items.inner[id] {
+        if let Some(item_idx) = self.backpack_items.get(id) {
             self.backpack_items.remove(id);
             self.active_items.push(item_idx);
         }
     }
 
     pub fn equip_spell(&mut self, id: usize) {
-        if let Some(spell_idx) = self.backpack_spells.inner[id] {
+        if let Some(spell_idx) = self.backpack_spells.get(id) {
             self.backpack_spells.remove(id);
             self.active_spells.push(spell_idx);
         }
     }
 
+    pub fn add_reward(&mut self, reward: Reward) {
+        match reward {
+            Reward::Item(item_idx) => self.backpack_items.push(item_idx),
+            Reward::Spell(spell_idx) => self.backpack_spells.push(spell_idx),
+            Reward::Sector(sector_idx) => self.backpack_sectors.push(sector_idx),
+        }
+    }
+
     pub fn get_spell_idx(&self, id: usize) -> Option<SpellIdx> {
-        self.active_spells.inner[id]
+        self.active_spells.get(id)
     }
 }
 
@@ -72,6 +83,12 @@ pub struct Stack<T: Copy, const N: usize> {
     inner: [Option<T>; N],
 }
 
+impl<T: Copy, const N: usize> Default for Stack<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Copy, const N: usize> Stack<T, N> {
     pub fn new() -> Self {
         let mut inner: [Option<T>; N] = unsafe { std::mem::MaybeUninit::zeroed().assume_init() };
@@ -86,6 +103,11 @@ impl<T: Copy, const N: usize> Stack<T, N> {
         self.inner[0] = Some(item);
     }
 
+    /// Returns `None` for empty and out of range positions.
+    pub fn get(&self, position: usize) -> Option<T> {
+        self.inner.get(position).copied().flatten()
+    }
+
     pub fn remove(&mut self, position: usize) {
         self.inner.copy_within(position + 1..N, position);
         self.inner[self.inner.len() - 1] = None;
@@ -106,22 +128,3 @@ impl<T: Copy, const N: usize> Index<usize> for Stack<T, N> {
 fn prepare_inventory(mut commands: Commands) {
     commands.insert_resource(Inventory::new());
 }
-
-fn on_sector_placed(
-    selected_section_button: Res<SelectedSectionButton>,
-    section_buttons: Query<&BackpackSectorId, With<UiImage>>,
-    mut inventory: ResMut<Inventory>,
-    mut event_reader: EventReader<SectorPlacedEvent>,
-    mut event_writer: EventWriter<InventoryUpdateEvent>,
-) {
-    for _ in event_reader.read() {
-        let Some(button_entity) = selected_section_button.0 else {
-            return;
-        };
-        let Ok(sector_id) = section_buttons.get(button_entity) else {
-            return;
-        };
-        inventory.backpack_sectors.remove(sector_id.0 as usize);
-        event_writer.send(InventoryUpdateEvent);
-    }
-}
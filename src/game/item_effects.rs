@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    animation::DAMAGE_COLOR_DEFAULT,
+    circle_sectors::HourCompleteEvent,
+    enemy::{BattleEnemy, DamageEnemyEvent, EnemyDeadEvent},
+    inventory::Inventory,
+    items::Items,
+    spells::EffectTarget,
+    stats::DamageType,
+    status_effects::{ApplyStatusEffectEvent, StatusEffectData},
+    AttackLandedEvent, BattleStartEvent, DamageTakenEvent, GameState, GameplaySet, Health, Player,
+    Shield,
+};
+
+pub struct ItemEffectsPlugin;
+
+impl Plugin for ItemEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ItemHookEvent>().add_systems(
+            FixedUpdate,
+            ((hook_combat, hook_hour_complete), trigger_item_effects)
+                .chain()
+                .run_if(state_exists::<GameState>)
+                .in_set(GameplaySet::Check),
+        );
+    }
+}
+
+/// Moments of the game items can react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum ItemHook {
+    /// The player attacked, the amount is the damage.
+    Hit,
+    /// The player lost health, the amount is the damage.
+    Damaged,
+    BattleStart,
+    /// The battle enemy died.
+    Kill,
+    HourComplete,
+}
+
+/// What an item does when its hook happens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ItemEffect {
+    Heal(f32),
+    /// Heals this part of the hook amount.
+    LifeSteal(f32),
+    /// Deals this part of the hook amount to the battle enemy.
+    Thorns(f32),
+    /// Raises max health and heals by the same amount.
+    MaxHealth(f32),
+    Shield(f32),
+    StatusEffect {
+        target: EffectTarget,
+        effect: StatusEffectData,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ItemTrigger {
+    pub on: ItemHook,
+    pub effect: ItemEffect,
+}
+
+/// Runs the triggers of the active items.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ItemHookEvent {
+    pub hook: ItemHook,
+    pub amount: f32,
+}
+
+fn hook_combat(
+    player: Query<Entity, With<Player>>,
+    mut battle_start_reader: EventReader<BattleStartEvent>,
+    mut attack_reader: EventReader<AttackLandedEvent>,
+    mut damage_taken_reader: EventReader<DamageTakenEvent>,
+    mut enemy_dead_reader: EventReader<EnemyDeadEvent>,
+    mut event_writer: EventWriter<ItemHookEvent>,
+) {
+    let Ok(player_entity) = player.get_single() else {
+        return;
+    };
+
+    for _ in battle_start_reader.read() {
+        event_writer.send(ItemHookEvent {
+            hook: ItemHook::BattleStart,
+            amount: 0.0,
+        });
+    }
+    for e in attack_reader.read() {
+        if e.attacker == player_entity {
+            event_writer.send(ItemHookEvent {
+                hook: ItemHook::Hit,
+                amount: e.damage,
+            });
+        }
+    }
+    for e in damage_taken_reader.read() {
+        if e.target == player_entity {
+            event_writer.send(ItemHookEvent {
+                hook: ItemHook::Damaged,
+                amount: e.damage,
+            });
+        }
+    }
+    for _ in enemy_dead_reader.read() {
+        event_writer.send(ItemHookEvent {
+            hook: ItemHook::Kill,
+            amount: 0.0,
+        });
+    }
+}
+
+fn hook_hour_complete(
+    mut event_reader: EventReader<HourCompleteEvent>,
+    mut event_writer: EventWriter<ItemHookEvent>,
+) {
+    for _ in event_reader.read() {
+        event_writer.send(ItemHookEvent {
+            hook: ItemHook::HourComplete,
+            amount: 0.0,
+        });
+    }
+}
+
+fn trigger_item_effects(
+    items: Res<Items>,
+    inventory: Res<Inventory>,
+    mut player: Query<(Entity, &mut Health, &mut Shield), With<Player>>,
+    enemy: Query<Entity, With<BattleEnemy>>,
+    mut event_reader: EventReader<ItemHookEvent>,
+    mut damage_enemy_event: EventWriter<DamageEnemyEvent>,
+    mut effect_event: EventWriter<ApplyStatusEffectEvent>,
+) {
+    let Ok((player_entity, mut player_health, mut player_shield)) = player.get_single_mut() else {
+        return;
+    };
+
+    for e in event_reader.read() {
+        let triggers = inventory
+            .active_items
+            .iter()
+            .flatten()
+            .flat_map(|item_idx| items[*item_idx].item.triggers())
+            .filter(|trigger| trigger.on == e.hook);
+
+        for trigger in triggers {
+            match trigger.effect {
+                ItemEffect::Heal(heal) => player_health.heal(heal),
+                ItemEffect::LifeSteal(part) => player_health.heal(e.amount * part),
+                ItemEffect::Thorns(part) => {
+                    if !enemy.is_empty() {
+                        damage_enemy_event.send(DamageEnemyEvent {
+                            damage: e.amount * part,
+                            color: DAMAGE_COLOR_DEFAULT,
+                            damage_type: DamageType::Physical,
+                            crit: false,
+                        });
+                    }
+                }
+                ItemEffect::MaxHealth(amount) => player_health.add_max(amount),
+                ItemEffect::Shield(shield) => player_shield.0 += shield,
+                ItemEffect::StatusEffect { target, effect } => {
+                    let target = match target {
+                        EffectTarget::Caster => Ok(player_entity),
+                        EffectTarget::Opponent => enemy.get_single(),
+                    };
+                    if let Ok(target) = target {
+                        effect_event.send(ApplyStatusEffectEvent { target, effect });
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,68 +1,101 @@
 use std::ops::{Index, IndexMut};
 
 use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    data::{
+        build_content_ids, CheckContent, ContentAsset, ContentHandle, ContentLoadedEvent,
+        RonAssetLoader,
+    },
+    item_effects::{ItemEffect, ItemTrigger},
+    stats::{Modifier, Stat},
+};
+
+/// Item catalog, relative to the assets folder.
+pub const ITEMS_DATA: &str = "data/items.ron";
 
 pub struct ItemsPlugin;
 
 impl Plugin for ItemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, prepare_items);
+        app.init_asset::<ItemsAsset>()
+            .register_asset_loader(RonAssetLoader::<ItemsAsset>::new(&["items.ron"]))
+            .add_systems(Startup, prepare_items)
+            .add_systems(Update, build_items.after(build_content_ids));
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ItemIdx(pub usize);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Item {
-    Coffecup,
-    Paperclip,
-    Plant,
-    Scissiors,
-    Stapler,
-    Stickynotes,
+/// Stat block of an item. Missing fields in the data file default to 0.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Item {
+    damage: f32,
+    defense: f32,
+    /// Stat modifiers on top of `damage` and `defense`.
+    modifiers: Vec<Modifier>,
+    triggers: Vec<ItemTrigger>,
 }
 
 impl Item {
     pub fn add_damage(&self) -> f32 {
-        match self {
-            Self::Coffecup => 0.0,
-            Self::Paperclip => 0.0,
-            Self::Plant => 0.0,
-            Self::Scissiors => 11.0,
-            Self::Stapler => 22.0,
-            Self::Stickynotes => 0.0,
-        }
+        self.damage
     }
 
     pub fn add_defense(&self) -> f32 {
-        match self {
-            Self::Coffecup => 0.0,
-            Self::Paperclip => 0.2,
-            Self::Plant => 0.0,
-            Self::Scissiors => 0.0,
-            Self::Stapler => 0.0,
-            Self::Stickynotes => 0.1,
-        }
+        self.defense
+    }
+
+    pub fn triggers(&self) -> impl Iterator<Item = &ItemTrigger> {
+        self.triggers.iter()
+    }
+
+    pub fn modifiers(&self) -> impl Iterator<Item = Modifier> + '_ {
+        [
+            Modifier::flat(Stat::Damage, self.damage),
+            Modifier::flat(Stat::Defense, self.defense),
+        ]
+        .into_iter()
+        .chain(self.modifiers.iter().copied())
     }
+}
+
+/// Single entry of the `assets/data/items.ron` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemData {
+    pub id: String,
+    pub description: String,
+    pub image: String,
+    #[serde(default)]
+    pub stats: Item,
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ItemsAsset {
+    pub items: Vec<ItemData>,
+}
 
-    pub fn heal(&self) -> f32 {
-        match self {
-            Self::Coffecup => 20.0,
-            Self::Paperclip => 0.0,
-            Self::Plant => 10.0,
-            Self::Scissiors => 0.0,
-            Self::Stapler => 0.0,
-            Self::Stickynotes => 0.0,
+impl CheckContent for ItemsAsset {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for item_data in self.items.iter() {
+            for trigger in item_data.stats.triggers() {
+                if let ItemEffect::StatusEffect { effect, .. } = trigger.effect {
+                    effect.check(&item_data.id, &mut problems);
+                }
+            }
         }
+        problems
     }
 }
 
 #[derive(Debug)]
 pub struct ItemInfo {
-    pub description: &'static str,
+    pub description: String,
     pub image: Handle<Image>,
-    pub drop_rate: f32,
     pub item: Item,
 }
 
@@ -83,50 +116,30 @@ impl IndexMut<ItemIdx> for Items {
 }
 
 fn prepare_items(asset_server: Res<AssetServer>, mut commands: Commands) {
-    let mut items = Items(vec![]);
-
-    // 0 coffee
-    items.0.push(ItemInfo {
-        description: "Smoking hot coffe for burnout nerves. Heals 20 hp after each battle.",
-        image: asset_server.load("items/item_coffecup.png"),
-        drop_rate: 0.2,
-        item: Item::Coffecup,
-    });
-    // 1 paperclip
-    items.0.push(ItemInfo {
-        description: "As paperclip holds papers, you hold your ground. Adds 20% defence.",
-        image: asset_server.load("items/item_paperclip.png"),
-        drop_rate: 0.9,
-        item: Item::Paperclip,
-    });
-    // 2 plant
-    items.0.push(ItemInfo {
-        description: "Decorative plant. Eat a leaf after each battle to restore 10 hp.",
-        image: asset_server.load("items/item_pot.png"),
-        drop_rate: 0.9,
-        item: Item::Plant,
-    });
-    // 3 scissors
-    items.0.push(ItemInfo {
-        description: "Scissors for cutting pay. Adds 11 damage.",
-        image: asset_server.load("items/item_scissors.png"),
-        drop_rate: 0.9,
-        item: Item::Scissiors,
-    });
-    // 4 stapler
-    items.0.push(ItemInfo {
-        description: "Stapler for closing your oppenent's arguments. Adds 22 damage.",
-        image: asset_server.load("items/item_stapler.png"),
-        drop_rate: 0.9,
-        item: Item::Stapler,
-    });
-    // 5 stickynotes
-    items.0.push(ItemInfo {
-        description: "With stickynotes you never forget about deadlines. Adds +10% defence.",
-        image: asset_server.load("items/item_stickynotes.png"),
-        drop_rate: 0.9,
-        item: Item::Stickynotes,
-    });
-
-    commands.insert_resource(items);
+    commands.insert_resource(ContentHandle::<ItemsAsset>(asset_server.load(ITEMS_DATA)));
+    // Filled by `build_items` once the data file is loaded.
+    commands.insert_resource(Items(vec![]));
+}
+
+fn build_items(
+    asset_server: Res<AssetServer>,
+    items_asset: ContentAsset<ItemsAsset>,
+    mut items: ResMut<Items>,
+    mut event_reader: EventReader<ContentLoadedEvent>,
+) {
+    for _ in event_reader.read() {
+        let Some(items_asset) = items_asset.get() else {
+            continue;
+        };
+
+        items.0 = items_asset
+            .items
+            .iter()
+            .map(|item_data| ItemInfo {
+                description: item_data.description.clone(),
+                image: asset_server.load(item_data.image.clone()),
+                item: item_data.stats.clone(),
+            })
+            .collect();
+    }
 }
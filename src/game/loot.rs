@@ -0,0 +1,184 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{circle_sectors::SectorIdx, data::ContentIds, items::ItemIdx, spells::SpellIdx};
+
+/// Weighted table of things that can drop. Each roll picks one of
+/// `entries` (or nothing) proportionally to the weights. Entries can
+/// themselves be tables.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct LootTable<T> {
+    /// How many times `entries` are rolled.
+    #[serde(default = "default_rolls")]
+    pub rolls: u32,
+    /// Weight of rolling nothing.
+    #[serde(default)]
+    pub nothing: f32,
+    /// Always dropped, on top of the rolls.
+    #[serde(default)]
+    pub guaranteed: Vec<Loot<T>>,
+    #[serde(default)]
+    pub entries: Vec<LootEntry<T>>,
+}
+
+fn default_rolls() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct LootEntry<T> {
+    pub weight: f32,
+    pub loot: Loot<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub enum Loot<T> {
+    Drop(T),
+    Table(LootTable<T>),
+}
+
+impl<T> Default for LootTable<T> {
+    fn default() -> Self {
+        Self {
+            rolls: default_rolls(),
+            nothing: 0.0,
+            guaranteed: vec![],
+            entries: vec![],
+        }
+    }
+}
+
+impl<T: Copy> LootTable<T> {
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<T> {
+        let mut drops = vec![];
+        self.roll_into(rng, &mut drops);
+        drops
+    }
+
+    fn roll_into<R: Rng>(&self, rng: &mut R, drops: &mut Vec<T>) {
+        for loot in self.guaranteed.iter() {
+            loot.roll_into(rng, drops);
+        }
+
+        let total = self.nothing + self.entries.iter().map(|e| e.weight).sum::<f32>();
+        if total <= 0.0 || !total.is_finite() {
+            return;
+        }
+        for _ in 0..self.rolls {
+            let mut pick = rng.gen_range(0.0..total);
+            for entry in self.entries.iter() {
+                if pick < entry.weight {
+                    entry.loot.roll_into(rng, drops);
+                    break;
+                }
+                pick -= entry.weight;
+            }
+        }
+    }
+}
+
+impl<T: Copy> Loot<T> {
+    fn roll_into<R: Rng>(&self, rng: &mut R, drops: &mut Vec<T>) {
+        match self {
+            Self::Drop(drop) => drops.push(*drop),
+            Self::Table(table) => table.roll_into(rng, drops),
+        }
+    }
+}
+
+impl<T> LootTable<T> {
+    /// Weights must be finite and not negative, or rolls can't pick.
+    pub fn check(&self, owner: &str, problems: &mut Vec<String>) {
+        if !(0.0..f32::INFINITY).contains(&self.nothing) {
+            problems.push(format!(
+                "{owner} has invalid weight of nothing {}",
+                self.nothing
+            ));
+        }
+        for loot in self.guaranteed.iter() {
+            loot.check(owner, problems);
+        }
+        for entry in self.entries.iter() {
+            if !(0.0..f32::INFINITY).contains(&entry.weight) {
+                problems.push(format!("{owner} has invalid weight {}", entry.weight));
+            }
+            entry.loot.check(owner, problems);
+        }
+    }
+
+    /// Converts every drop with `f`. Drops it returns `None` for are left out.
+    pub fn filter_map<U>(&self, f: &mut dyn FnMut(&T) -> Option<U>) -> LootTable<U> {
+        LootTable {
+            rolls: self.rolls,
+            nothing: self.nothing,
+            guaranteed: self
+                .guaranteed
+                .iter()
+                .filter_map(|loot| loot.filter_map(f))
+                .collect(),
+            entries: self
+                .entries
+                .iter()
+                .filter_map(|entry| {
+                    Some(LootEntry {
+                        weight: entry.weight,
+                        loot: entry.loot.filter_map(f)?,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T> Loot<T> {
+    fn check(&self, owner: &str, problems: &mut Vec<String>) {
+        if let Self::Table(table) = self {
+            table.check(owner, problems);
+        }
+    }
+
+    fn filter_map<U>(&self, f: &mut dyn FnMut(&T) -> Option<U>) -> Option<Loot<U>> {
+        match self {
+            Self::Drop(drop) => f(drop).map(Loot::Drop),
+            Self::Table(table) => Some(Loot::Table(table.filter_map(f))),
+        }
+    }
+}
+
+/// Loot of chests and enemies, goes into the backpack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reward {
+    Item(ItemIdx),
+    Spell(SpellIdx),
+    Sector(SectorIdx),
+}
+
+/// `Reward` as written in the data files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum RewardId {
+    Item(String),
+    Spell(String),
+    Sector(String),
+}
+
+impl RewardId {
+    pub fn resolve(&self, content_ids: &ContentIds, owner: &str) -> Option<Reward> {
+        match self {
+            Self::Item(id) => content_ids.items.resolve(owner, id).map(Reward::Item),
+            Self::Spell(id) => content_ids.spells.resolve(owner, id).map(Reward::Spell),
+            Self::Sector(id) => content_ids.sectors.resolve(owner, id).map(Reward::Sector),
+        }
+    }
+}
+
+/// Resolves ids of a reward table owned by `owner`.
+pub fn resolve_rewards(
+    content_ids: &ContentIds,
+    owner: &str,
+    table: &LootTable<RewardId>,
+) -> LootTable<Reward> {
+    table.filter_map(&mut |reward_id| reward_id.resolve(content_ids, owner))
+}
@@ -2,32 +2,58 @@ use bevy::prelude::*;
 
 use crate::GlobalState;
 
+pub mod actions;
 pub mod animation;
 pub mod chest;
 pub mod circle_sectors;
 pub mod cursor;
+pub mod data;
 pub mod enemy;
 pub mod hp_bar;
+pub mod interpolation;
 pub mod inventory;
+pub mod item_effects;
 pub mod items;
+pub mod loot;
+pub mod music;
 pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod settings;
 pub mod sound;
 pub mod spells;
+pub mod stats;
+pub mod status_effects;
 
+use actions::{ActionsPlugin, PlayerAction, PlayerActionEvent};
 use animation::AnimationPlugin;
 use chest::{Chest, ChestOppenedEvent, ChestsPlugin, InteractedChest};
 use circle_sectors::{position_to_sector_position, SectorPosition, SectorsPlugin};
 use cursor::CursorPlugin;
+use data::ContentPlugin;
 use enemy::{BattleEnemy, Enemy, EnemyDeadEvent, EnemyPlugin};
 use hp_bar::{HpBarPlugin, HpBarResources};
-use inventory::{Inventory, InventoryPlugin};
-use items::{Items, ItemsPlugin};
+use interpolation::{interpolate_transforms, InterpolationPlugin};
+use inventory::InventoryPlugin;
+use item_effects::ItemEffectsPlugin;
+use items::ItemsPlugin;
+use music::MusicPlugin;
 use player::{spawn_player, Player, PlayerPlugin, PlayerResources, PlayerState};
+use replay::ReplayPlugin;
+use rng::RngPlugin;
+use save::SavePlugin;
+use settings::GameSettingsPlugin;
 use sound::SoundPlugin;
 use spells::SpellsPlugin;
+use stats::StatsPlugin;
+use status_effects::StatusEffectsPlugin;
 
 const INTERACTION_DISTANCE: f32 = 30.0;
 
+/// Distance of the player from the center of the clock.
+pub const PLAYER_RING_RADIUS: f32 = 230.0;
+
 pub const Z_WALL: f32 = 0.0;
 pub const Z_SECTORS: f32 = 1.0;
 pub const Z_CLOCK_CENTER: f32 = 2.0;
@@ -41,24 +67,46 @@ pub const Z_PLAYER: f32 = 5.0;
 
 const CAMERA_FOLLOW_SPEED: f32 = 8.0;
 
+/// Base damage multiplier of crits.
+pub const CRIT_MULTIPLIER: f32 = 1.5;
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
-            AnimationPlugin,
+            (ActionsPlugin, AnimationPlugin, InterpolationPlugin),
             ChestsPlugin,
             SectorsPlugin,
+            ContentPlugin,
             CursorPlugin,
             EnemyPlugin,
             HpBarPlugin,
             InventoryPlugin,
-            ItemsPlugin,
+            (ItemEffectsPlugin, ItemsPlugin),
             PlayerPlugin,
-            SoundPlugin,
-            SpellsPlugin,
+            (ReplayPlugin, RngPlugin, SavePlugin),
+            (GameSettingsPlugin, MusicPlugin, SoundPlugin),
+            (SpellsPlugin, StatsPlugin, StatusEffectsPlugin),
         ))
+        .add_event::<BattleStartEvent>()
+        .add_event::<AttackLandedEvent>()
+        .add_event::<DamageTakenEvent>()
         .add_sub_state::<GameState>()
+        .configure_sets(
+            FixedUpdate,
+            (
+                GameplaySet::Move,
+                GameplaySet::Spawn,
+                GameplaySet::Tick,
+                GameplaySet::Attack,
+                GameplaySet::Damage,
+                GameplaySet::Check,
+                GameplaySet::Transition,
+            )
+                .chain(),
+        )
+        .add_systems(FixedPostUpdate, apply_state_transitions)
         .add_systems(Startup, setup_camera)
         .add_systems(OnEnter(GameState::Preparing), spawn_base_game)
         .add_systems(OnEnter(GlobalState::MainMenu), camera_target_main_menu)
@@ -66,13 +114,16 @@ impl Plugin for GamePlugin {
         .add_systems(OnEnter(GameState::Battle), camera_target_player)
         .add_systems(OnEnter(GameState::Paused), camera_target_pause)
         .add_systems(
-            Update,
-            (initiate_battle, initiate_pickup).run_if(in_state(GameState::Running)),
+            FixedUpdate,
+            (
+                (initiate_battle, initiate_pickup).run_if(in_state(GameState::Running)),
+                battle_end_check.run_if(in_state(GameState::Battle)),
+                pickup_end_check.run_if(in_state(GameState::Pickup)),
+            )
+                .in_set(GameplaySet::Transition),
         )
-        .add_systems(Update, battle_end_check.run_if(in_state(GameState::Battle)))
-        .add_systems(Update, pickup_end_check.run_if(in_state(GameState::Pickup)))
         .add_systems(Update, game_pause.run_if(state_exists::<GameState>))
-        .add_systems(Update, camera_follow_target);
+        .add_systems(Update, camera_follow_target.after(interpolate_transforms));
     }
 }
 
@@ -89,6 +140,25 @@ pub enum GameState {
     GameOver,
 }
 
+/// Gameplay runs in `FixedUpdate` in this order, so a step resolves the
+/// same way whatever the frame rate.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    /// Player movement.
+    Move,
+    /// Progress on the clock and things appearing in sectors.
+    Spawn,
+    /// Animations, attack timers and cooldowns.
+    Tick,
+    /// Attacks and spells that land.
+    Attack,
+    Damage,
+    /// Deaths, rewards and opened chests.
+    Check,
+    /// Battles and pickups starting or ending.
+    Transition,
+}
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GameCamera {
     target: Entity,
@@ -120,6 +190,10 @@ impl Health {
         self.current
     }
 
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
     pub fn percent(&self) -> f32 {
         self.current / self.max
     }
@@ -131,6 +205,43 @@ impl Health {
     pub fn heal(&mut self, heal: f32) {
         self.current = (self.current + heal).min(self.max);
     }
+
+    /// Raises max health and heals by the same amount.
+    pub fn add_max(&mut self, amount: f32) {
+        self.max += amount;
+        self.heal(amount);
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BattleStartEvent;
+
+/// Sent for every attack of the player or the battle enemy.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct AttackLandedEvent {
+    pub attacker: Entity,
+    pub damage: f32,
+}
+
+/// Damage that got through defense and shield.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageTakenEvent {
+    pub target: Entity,
+    pub damage: f32,
+}
+
+/// Flat amount of damage taken before `Health`. Lasts until the battle
+/// ends.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Shield(pub f32);
+
+impl Shield {
+    /// Returns the absorbed part of the damage.
+    pub fn absorb(&mut self, damage: f32) -> f32 {
+        let absorbed = damage.min(self.0).max(0.0);
+        self.0 -= absorbed;
+        absorbed
+    }
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq)]
@@ -139,6 +250,22 @@ pub struct Damage(pub f32);
 #[derive(Component, Debug, Clone, Copy, PartialEq)]
 pub struct Defense(pub f32);
 
+/// Chance of an attack to crit and the damage multiplier of crits.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Crit {
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+impl Crit {
+    pub fn new(chance: f32) -> Self {
+        Self {
+            chance,
+            multiplier: CRIT_MULTIPLIER,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone, PartialEq)]
 pub struct AttackSpeed(pub Timer);
 
@@ -257,26 +384,26 @@ fn spawn_base_game(
         &mut commands,
         player_resources.as_ref(),
         hp_bar_resources.as_ref(),
-        Transform::from_xyz(0.0, 230.0, Z_PLAYER).with_scale(Vec3::new(2.0, 2.0, 2.0)),
+        Transform::from_xyz(0.0, PLAYER_RING_RADIUS, Z_PLAYER).with_scale(Vec3::new(2.0, 2.0, 2.0)),
     );
 
     game_state.set(GameState::Running);
     player_state.set(PlayerState::Run);
 }
 
+/// States normally change once per frame. Changing them after every fixed
+/// step too keeps e.g. a finished battle from running for extra steps on
+/// slow frames.
+fn apply_state_transitions(world: &mut World) {
+    let _ = world.try_run_schedule(StateTransition);
+}
+
 fn game_pause(
     key_input: Res<ButtonInput<KeyCode>>,
-    game_state: Res<State<GameState>>,
-    mut game_state_next: ResMut<NextState<GameState>>,
-    mut local: Local<GameState>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
 ) {
     if key_input.just_pressed(KeyCode::Space) {
-        if game_state.get() == &GameState::Paused {
-            game_state_next.set(*local);
-        } else {
-            *local = *game_state.get();
-            game_state_next.set(GameState::Paused);
-        }
+        event_writer.send(PlayerActionEvent(PlayerAction::TogglePause));
     }
 }
 
@@ -286,6 +413,7 @@ fn initiate_battle(
     mut commands: Commands,
     mut game_sate: ResMut<NextState<GameState>>,
     mut player_state: ResMut<NextState<PlayerState>>,
+    mut event_writer: EventWriter<BattleStartEvent>,
 ) {
     let Ok(player_transform) = player.get_single() else {
         return;
@@ -306,37 +434,25 @@ fn initiate_battle(
                 .insert(BattleEnemy);
 
             game_sate.set(GameState::Battle);
+            event_writer.send(BattleStartEvent);
             player_state.set(PlayerState::Idle);
         }
     }
 }
 
 fn battle_end_check(
-    items: Res<Items>,
-    inventory: Res<Inventory>,
-    mut player: Query<(&mut Health, &mut AttackSpeed), With<Player>>,
+    mut player: Query<(&mut Shield, &mut AttackSpeed), With<Player>>,
     mut player_state: ResMut<NextState<PlayerState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut event_reader: EventReader<EnemyDeadEvent>,
 ) {
-    let Ok((mut player_health, mut player_attack_speed)) = player.get_single_mut() else {
+    let Ok((mut player_shield, mut player_attack_speed)) = player.get_single_mut() else {
         return;
     };
     for _ in event_reader.read() {
         info!("game battle end");
         player_attack_speed.0.reset();
-        let heal = inventory
-            .active_items
-            .iter()
-            .map(|item_idx| {
-                if let Some(i) = item_idx {
-                    items[*i].item.heal()
-                } else {
-                    0.0
-                }
-            })
-            .sum::<f32>();
-        player_health.heal(heal);
+        player_shield.0 = 0.0;
 
         game_state.set(GameState::Running);
         player_state.set(PlayerState::Run);
@@ -0,0 +1,167 @@
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use super::{
+    circle_sectors::LastBossTag,
+    enemy::{BattleEnemy, Enemy},
+    settings::Settings,
+    sound::DucksMusic,
+    GameState,
+};
+
+/// Time for one track to fully replace another.
+const CROSSFADE_SECONDS: f32 = 1.5;
+/// Music volume while a ducking sound plays.
+const DUCKED_VOLUME: f32 = 0.4;
+/// Time to duck the music and to bring it back.
+const DUCK_SECONDS: f32 = 0.25;
+/// Music volume while the game is paused.
+const PAUSED_VOLUME: f32 = 0.4;
+
+const MUSIC_TRACKS: [(MusicTrack, &str); 3] = [
+    (MusicTrack::Exploration, "sounds/background.ogg"),
+    (MusicTrack::Battle, "sounds/music_battle.ogg"),
+    (MusicTrack::Boss, "sounds/music_boss.ogg"),
+];
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicDirector>()
+            .add_systems(Startup, prepare_music)
+            .add_systems(Update, (choose_music_track, update_music).chain());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Exploration,
+    Battle,
+    Boss,
+}
+
+/// Track that should be playing. Others fade out.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct MusicDirector {
+    /// No music after the run ends, the win and game over stingers play
+    /// over the faded out tracks.
+    pub track: Option<MusicTrack>,
+    duck: f32,
+}
+
+impl Default for MusicDirector {
+    fn default() -> Self {
+        Self {
+            track: Some(MusicTrack::Exploration),
+            duck: 1.0,
+        }
+    }
+}
+
+/// Every track loops and is only faded in and out, so switching back
+/// continues where the track was. Silent tracks are paused.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct MusicLayer {
+    pub track: MusicTrack,
+    fade: f32,
+}
+
+fn prepare_music(asset_server: Res<AssetServer>, mut commands: Commands) {
+    for (track, path) in MUSIC_TRACKS {
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(path),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::new(0.0),
+                    paused: true,
+                    ..Default::default()
+                },
+            },
+            MusicLayer { track, fade: 0.0 },
+        ));
+    }
+}
+
+fn choose_music_track(
+    game_state: Option<Res<State<GameState>>>,
+    battle_enemy: Query<&Enemy, With<BattleEnemy>>,
+    last_boss: Query<(), With<LastBossTag>>,
+    mut director: ResMut<MusicDirector>,
+) {
+    let track = match game_state.map(|game_state| *game_state.get()) {
+        None => Some(MusicTrack::Exploration),
+        Some(GameState::Win | GameState::GameOver) => None,
+        // Keeps the track, only quieter.
+        Some(GameState::Paused) => director.track,
+        Some(GameState::Battle) if battle_enemy.iter().any(|enemy| enemy.is_boss) => {
+            Some(MusicTrack::Boss)
+        }
+        Some(GameState::Battle) => Some(MusicTrack::Battle),
+        // The boss waits on the clock after the last cycle.
+        Some(_) if !last_boss.is_empty() => Some(MusicTrack::Boss),
+        Some(_) => Some(MusicTrack::Exploration),
+    };
+    if director.track != track {
+        director.track = track;
+    }
+}
+
+/// Fades use real time, so they also run while the game is paused.
+fn update_music(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    game_state: Option<Res<State<GameState>>>,
+    ducking: Query<(), With<DucksMusic>>,
+    mut director: ResMut<MusicDirector>,
+    mut layers: Query<(&mut MusicLayer, Option<&AudioSink>)>,
+) {
+    let dt = time.delta_seconds();
+
+    let duck_target = if ducking.is_empty() {
+        1.0
+    } else {
+        DUCKED_VOLUME
+    };
+    let duck = move_towards(
+        director.duck,
+        duck_target,
+        (1.0 - DUCKED_VOLUME) * dt / DUCK_SECONDS,
+    );
+    if director.duck != duck {
+        director.duck = duck;
+    }
+
+    let paused = game_state.is_some_and(|game_state| *game_state.get() == GameState::Paused);
+    let paused_volume = if paused { PAUSED_VOLUME } else { 1.0 };
+
+    for (mut layer, sink) in layers.iter_mut() {
+        let target = if director.track == Some(layer.track) {
+            1.0
+        } else {
+            0.0
+        };
+        layer.fade = move_towards(layer.fade, target, dt / CROSSFADE_SECONDS);
+
+        let Some(sink) = sink else {
+            continue;
+        };
+        if layer.fade == 0.0 {
+            if !sink.is_paused() {
+                sink.pause();
+            }
+            continue;
+        }
+        if sink.is_paused() {
+            sink.play();
+        }
+        sink.set_volume(settings.music() * layer.fade * director.duck * paused_volume);
+    }
+}
+
+fn move_towards(current: f32, target: f32, max_delta: f32) -> f32 {
+    current + (target - current).clamp(-max_delta, max_delta)
+}
@@ -1,20 +1,28 @@
-use bevy::{audio::PlaybackMode, ecs::system::EntityCommands, prelude::*};
+use bevy::{ecs::system::EntityCommands, prelude::*};
 
 use crate::{ui::UiStyle, GlobalState};
 
 use super::{
     animation::{
         spawn_damage_text, AllAnimations, AnimationConfig, AnimationFinishedEvent,
-        DAMAGE_COLOR_DEFAULT,
+        DAMAGE_COLOR_SHIELD,
     },
     enemy::DamageEnemyEvent,
-    hp_bar::{hp_bar_bundle, HpBarResources},
-    inventory::Inventory,
-    items::Items,
-    sound::SoundResources,
-    AttackSpeed, Damage, Defense, GameCameraPossibleTarget, GameState, Health,
+    hp_bar::{hp_bar_bundle, shield_bar_bundle, HpBarResources},
+    interpolation::InterpolatedTransform,
+    rng::RunRng,
+    sound::{SoundCue, SoundCueEvent},
+    stats::{DamageType, Stats},
+    status_effects::{
+        apply_status_effects, StatusEffectAppliedEvent, StatusEffectKind, StatusEffects,
+    },
+    AttackLandedEvent, AttackSpeed, Crit, Damage, DamageTakenEvent, Defense,
+    GameCameraPossibleTarget, GameState, GameplaySet, Health, Shield,
 };
 
+/// Chance of a player attack to crit without items.
+const PLAYER_CRIT_CHANCE: f32 = 0.05;
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
@@ -26,17 +34,34 @@ impl Plugin for PlayerPlugin {
             .add_systems(OnEnter(PlayerState::Run), player_start_run)
             .add_systems(OnEnter(PlayerState::Attack), player_start_attack)
             .add_systems(OnEnter(PlayerState::Dead), player_start_dead)
-            .add_systems(Update, player_run.run_if(in_state(GameState::Running)))
             .add_systems(
-                Update,
-                (player_attack, on_attack_finish, player_take_damage)
+                FixedUpdate,
+                player_run
+                    .run_if(in_state(GameState::Running))
+                    .in_set(GameplaySet::Move),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (player_crowd_control, player_attack)
+                        .chain()
+                        .after(apply_status_effects)
+                        .in_set(GameplaySet::Tick),
+                    on_attack_finish.in_set(GameplaySet::Attack),
+                    player_take_damage.in_set(GameplaySet::Damage),
+                )
                     .run_if(in_state(GameState::Battle)),
             );
     }
 }
 
 #[derive(Event, Debug, Clone, PartialEq)]
-pub struct DamagePlayerEvent(pub f32);
+pub struct DamagePlayerEvent {
+    pub damage: f32,
+    pub color: Color,
+    pub damage_type: DamageType,
+    pub crit: bool,
+}
 
 #[derive(Resource, Debug)]
 pub struct PlayerResources {
@@ -135,12 +160,16 @@ pub fn spawn_player<'a>(
         },
         player_resources.texture_atlas.clone(),
         player_resources.idle_animation_config.clone(),
+        InterpolatedTransform::new(transform),
         Player,
         PlayerSpeed(0.5),
         Health::new(200.0),
         Damage(10.0),
         AttackSpeed::new(0.5),
         Defense(0.0),
+        Crit::new(PLAYER_CRIT_CHANCE),
+        Stats::default(),
+        Shield::default(),
         GameCameraPossibleTarget {
             scale: Vec3::new(0.5, 0.5, 0.5),
         },
@@ -149,6 +178,7 @@ pub fn spawn_player<'a>(
     let parent_entity = c.id();
     c.with_children(|builder| {
         builder.spawn(hp_bar_bundle(hp_bar_resources, parent_entity));
+        builder.spawn(shield_bar_bundle(hp_bar_resources, parent_entity));
     });
     c
 }
@@ -218,17 +248,43 @@ fn player_run(time: Res<Time>, mut player: Query<(&PlayerSpeed, &mut Transform)>
     transform.rotation *= rotation;
 }
 
+/// Cancels the attack in progress of a player stunned or interrupted by
+/// an enemy spell.
+fn player_crowd_control(
+    player_state: Res<State<PlayerState>>,
+    mut player: Query<(Entity, &mut AttackSpeed), With<Player>>,
+    mut player_next_state: ResMut<NextState<PlayerState>>,
+    mut event_reader: EventReader<StatusEffectAppliedEvent>,
+) {
+    let Ok((player_entity, mut player_attack_speed)) = player.get_single_mut() else {
+        return;
+    };
+
+    for e in event_reader.read() {
+        if e.target != player_entity || !e.kind.is_crowd_control() {
+            continue;
+        }
+        if e.kind == StatusEffectKind::Interrupt {
+            player_attack_speed.0.reset();
+        }
+        if player_state.get() == &PlayerState::Attack {
+            player_next_state.set(PlayerState::Idle);
+        }
+    }
+}
+
 fn player_attack(
     time: Res<Time>,
     player_state: Res<State<PlayerState>>,
-    mut player: Query<&mut AttackSpeed, With<Player>>,
+    status_effects: StatusEffects,
+    mut player: Query<(Entity, &mut AttackSpeed), With<Player>>,
     mut player_next_state: ResMut<NextState<PlayerState>>,
 ) {
-    let Ok(mut player_attack_speed) = player.get_single_mut() else {
+    let Ok((player_entity, mut player_attack_speed)) = player.get_single_mut() else {
         return;
     };
 
-    if player_state.get() != &PlayerState::Idle {
+    if player_state.get() != &PlayerState::Idle || status_effects.is_stunned(player_entity) {
         return;
     }
 
@@ -239,47 +295,32 @@ fn player_attack(
 }
 
 fn on_attack_finish(
-    items: Res<Items>,
-    inventory: Res<Inventory>,
-    player: Query<&Damage, With<Player>>,
-    sounds: Res<SoundResources>,
-    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    player: Query<(Entity, &Stats), With<Player>>,
     mut event_reader: EventReader<AnimationFinishedEvent>,
     mut event_writer: EventWriter<DamageEnemyEvent>,
+    mut attack_event: EventWriter<AttackLandedEvent>,
+    mut sound_event: EventWriter<SoundCueEvent>,
     mut player_state: ResMut<NextState<PlayerState>>,
 ) {
-    let Ok(player_damage) = player.get_single() else {
+    let Ok((player_entity, player_stats)) = player.get_single() else {
         return;
     };
 
     for e in event_reader.read() {
         if e.0 == AllAnimations::PlayerAttack {
-            // Attack sound
-            commands.spawn(AudioBundle {
-                source: sounds.player_attack.clone(),
-                settings: PlaybackSettings {
-                    mode: PlaybackMode::Despawn,
-                    volume: sounds.volume,
-                    ..Default::default()
-                },
-            });
-
-            let damage = player_damage.0
-                + inventory
-                    .active_items
-                    .iter()
-                    .map(|item_idx| {
-                        if let Some(i) = item_idx {
-                            items[*i].item.add_damage()
-                        } else {
-                            0.0
-                        }
-                    })
-                    .sum::<f32>();
+            sound_event.send(SoundCueEvent(SoundCue::PlayerAttack));
 
+            let (damage, crit) = player_stats.roll_crit(player_stats.damage(), &mut run_rng.combat);
             event_writer.send(DamageEnemyEvent {
                 damage,
                 color: Color::srgb(1.0, 0.0, 0.0),
+                damage_type: DamageType::Physical,
+                crit,
+            });
+            attack_event.send(AttackLandedEvent {
+                attacker: player_entity,
+                damage,
             });
             player_state.set(PlayerState::Idle);
         }
@@ -287,43 +328,53 @@ fn on_attack_finish(
 }
 
 fn player_take_damage(
-    items: Res<Items>,
-    inventory: Res<Inventory>,
     ui_style: Res<UiStyle>,
     mut commands: Commands,
     mut game_state: ResMut<NextState<GameState>>,
-    mut player: Query<(&Transform, &Defense, &mut Health), With<Player>>,
+    mut player: Query<(Entity, &Transform, &Stats, &mut Shield, &mut Health), With<Player>>,
     mut event_read: EventReader<DamagePlayerEvent>,
+    mut damage_taken_event: EventWriter<DamageTakenEvent>,
 ) {
-    let Ok((player_transform, player_defense, mut player_health)) = player.get_single_mut() else {
+    let Ok((player_entity, player_transform, player_stats, mut player_shield, mut player_health)) =
+        player.get_single_mut()
+    else {
         return;
     };
 
     for e in event_read.read() {
-        let player_defense = player_defense.0
-            + inventory
-                .active_items
-                .iter()
-                .map(|item_idx| {
-                    if let Some(i) = item_idx {
-                        items[*i].item.add_defense()
-                    } else {
-                        0.0
-                    }
-                })
-                .sum::<f32>();
-
-        let damage = e.0 * (1.0 - player_defense);
+        let damage = e.damage * (1.0 - player_stats.defense());
+        let absorbed = player_shield.absorb(damage);
+        let damage = damage - absorbed;
         player_health.take_damage(damage);
+        if 0.0 < damage {
+            damage_taken_event.send(DamageTakenEvent {
+                target: player_entity,
+                damage,
+            });
+        }
 
-        spawn_damage_text(
-            &mut commands,
-            ui_style.as_ref(),
-            damage,
-            *player_transform,
-            player_transform.translation.normalize(),
-            DAMAGE_COLOR_DEFAULT,
-        );
+        if 0.0 < absorbed {
+            spawn_damage_text(
+                &mut commands,
+                ui_style.as_ref(),
+                absorbed,
+                *player_transform,
+                player_transform.translation.normalize(),
+                DAMAGE_COLOR_SHIELD,
+                e.crit,
+            );
+        }
+        if 0.0 < damage || absorbed == 0.0 {
+            spawn_damage_text(
+                &mut commands,
+                ui_style.as_ref(),
+                damage,
+                *player_transform,
+                player_transform.translation.normalize(),
+                e.color,
+                e.crit,
+            );
+        }
 
         if player_health.current() == 0.0 {
             game_state.set(GameState::GameOver);
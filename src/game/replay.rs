@@ -0,0 +1,245 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    app::{FixedMainScheduleOrder, MainScheduleOrder},
+    ecs::schedule::ExecutorKind,
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::GlobalState;
+
+use super::{
+    actions::{apply_player_actions, PlayerAction, PlayerActionEvent},
+    rng::{RunRng, RunSeed},
+    GameState,
+};
+
+/// Bumped when old replays can no longer be played back.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Simulated time of one frame while recording or playing back.
+const REPLAY_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayMode>()
+            .add_systems(
+                OnEnter(GlobalState::MainMenu),
+                skip_main_menu.run_if(resource_exists::<ReplayPlayer>),
+            )
+            .add_systems(
+                OnEnter(GlobalState::InGame),
+                (
+                    start_recording.run_if(resource_exists::<ReplayRecorder>),
+                    start_playback.run_if(resource_exists::<ReplayPlayer>),
+                ),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    play_replay
+                        .before(apply_player_actions)
+                        .run_if(resource_exists::<ReplayPlayer>),
+                    record_replay
+                        .after(apply_player_actions)
+                        .run_if(resource_exists::<ReplayRecorder>),
+                )
+                    .run_if(state_exists::<GameState>),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let mode = app.world().resource::<ReplayMode>().clone();
+        let step = match mode {
+            ReplayMode::Off => return,
+            ReplayMode::Record(path) => {
+                app.insert_resource(ReplayRecorder {
+                    path,
+                    replay: Replay::new(0, REPLAY_STEP),
+                    run_start: Duration::ZERO,
+                });
+                REPLAY_STEP
+            }
+            ReplayMode::Playback(path) => {
+                let replay = match Replay::load(&path) {
+                    Ok(replay) => replay,
+                    Err(e) => {
+                        error!("can't play back {}: {e}", path.display());
+                        return;
+                    }
+                };
+                info!("playing back {}", path.display());
+                let step = replay.step;
+                app.insert_resource(RunSeed(Some(replay.seed)))
+                    .insert_resource(ReplayPlayer {
+                        replay,
+                        next: 0,
+                        run_start: Duration::ZERO,
+                    });
+                step
+            }
+        };
+
+        // A run only repeats if every frame has the same length and the
+        // systems run in the same order.
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+        let world = app.world();
+        let main_labels = world.resource::<MainScheduleOrder>().labels.iter();
+        let fixed_labels = world.resource::<FixedMainScheduleOrder>().labels.iter();
+        let labels = main_labels.chain(fixed_labels).copied().collect::<Vec<_>>();
+        for label in labels {
+            app.edit_schedule(label, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            });
+        }
+    }
+}
+
+/// Set on startup, e.g. from `--record <file>` or `--replay <file>`.
+///
+/// Both run the game on a fixed step of 1/60 s, so while recording the
+/// game runs slower or faster if the frame rate differs from 60 fps.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    /// Writes every player action of the run to the file.
+    Record(PathBuf),
+    /// Plays the run from the file instead of the player.
+    Playback(PathBuf),
+}
+
+/// Everything needed to play a run again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    /// Simulated time of one frame.
+    pub step: Duration,
+    pub actions: Vec<ReplayAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayAction {
+    /// Game time since the start of the run.
+    pub time: Duration,
+    pub action: PlayerAction,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ron::error::SpannedError),
+    #[error(transparent)]
+    Write(#[from] ron::Error),
+    #[error("replay version {0} is not supported, expected {REPLAY_VERSION}")]
+    Version(u32),
+    #[error("replay action {:?} at {:?} is outside of the inventory", .0.action, .0.time)]
+    Action(ReplayAction),
+}
+
+impl Replay {
+    pub fn new(seed: u64, step: Duration) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            step,
+            actions: vec![],
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let replay = ron::from_str::<Self>(&std::fs::read_to_string(path)?)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        if let Some(action) = replay.actions.iter().find(|a| !a.action.in_bounds()) {
+            return Err(ReplayError::Action(*action));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[derive(Resource, Debug)]
+struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+    run_start: Duration,
+}
+
+#[derive(Resource, Debug)]
+struct ReplayPlayer {
+    replay: Replay,
+    next: usize,
+    run_start: Duration,
+}
+
+fn skip_main_menu(mut global_state: ResMut<NextState<GlobalState>>) {
+    global_state.set(GlobalState::InGame);
+}
+
+fn start_recording(time: Res<Time>, run_rng: Res<RunRng>, mut recorder: ResMut<ReplayRecorder>) {
+    recorder.replay = Replay::new(run_rng.seed(), REPLAY_STEP);
+    recorder.run_start = time.elapsed();
+    if let Err(e) = recorder.replay.save(&recorder.path) {
+        error!("can't write replay {}: {e}", recorder.path.display());
+    }
+}
+
+fn start_playback(time: Res<Time>, mut player: ResMut<ReplayPlayer>) {
+    player.next = 0;
+    player.run_start = time.elapsed();
+}
+
+/// Saved after every action, so the replay survives a crash.
+fn record_replay(
+    time: Res<Time>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut event_reader: EventReader<PlayerActionEvent>,
+) {
+    let time = time.elapsed() - recorder.run_start;
+    let mut recorded = false;
+    for e in event_reader.read() {
+        recorder
+            .replay
+            .actions
+            .push(ReplayAction { time, action: e.0 });
+        recorded = true;
+    }
+    if recorded {
+        if let Err(e) = recorder.replay.save(&recorder.path) {
+            error!("can't write replay {}: {e}", recorder.path.display());
+        }
+    }
+}
+
+fn play_replay(
+    time: Res<Time>,
+    mut player: ResMut<ReplayPlayer>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
+) {
+    let time = time.elapsed() - player.run_start;
+    while let Some(replay_action) = player.replay.actions.get(player.next) {
+        if time < replay_action.time {
+            break;
+        }
+        event_writer.send(PlayerActionEvent(replay_action.action));
+        player.next += 1;
+    }
+}
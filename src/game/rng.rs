@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::GlobalState;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSeed>()
+            .add_systems(OnEnter(GlobalState::MainMenu), seed_run_rng);
+    }
+}
+
+/// Seed of the next run. A random one is picked if not set.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RunSeed(pub Option<u64>);
+
+/// Source of all randomness of a run. Each stream is independent, so
+/// e.g. extra loot rolls don't change what spawns next.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRng {
+    seed: u64,
+    /// Enemies and chests appearing in sectors.
+    pub spawn: ChaCha8Rng,
+    /// Rewards from enemies and chests.
+    pub loot: ChaCha8Rng,
+    /// Hit chances in battles.
+    pub combat: ChaCha8Rng,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        let stream = |stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng
+        };
+        Self {
+            seed,
+            spawn: stream(0),
+            loot: stream(1),
+            combat: stream(2),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// The clock of the next run is built on entering the main menu, so the
+/// run starts there.
+pub fn seed_run_rng(run_seed: Res<RunSeed>, mut commands: Commands) {
+    let seed = run_seed.0.unwrap_or_else(rand::random);
+    info!("run seed: {seed}");
+    commands.insert_resource(RunRng::new(seed));
+}
@@ -0,0 +1,487 @@
+use std::{
+    f32::consts::PI,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    chest::{ChestIdx, ChestResources},
+    circle_sectors::{
+        spawn_last_boss, spawn_sector_thing, HourCompleteEvent, LastBossTag, PlayerProgress,
+        SectorIdx, SectorPosition, SectorSlotEntity, SectorSlots, SectorSpawn, SectorTimer,
+        Sectors, SECTOR_THINGS,
+    },
+    data::{ContentIds, IdMap},
+    enemy::{BattleEnemyDead, Enemies, Enemy, EnemyIdx},
+    hp_bar::HpBarResources,
+    interpolation::InterpolatedTransform,
+    inventory::{Inventory, InventoryUpdateEvent, Stack},
+    player::Player,
+    rng::RunRng,
+    spawn_base_game,
+    spells::Spells,
+    Damage, GameState, Health, PLAYER_RING_RADIUS, Z_PLAYER,
+};
+
+/// Bumped when old saves can no longer be resumed.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Default save file, relative to the working directory.
+pub const SAVE_FILE: &str = "save.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveRunEvent>()
+            .add_systems(
+                OnEnter(GameState::Preparing),
+                restore_run
+                    .after(spawn_base_game)
+                    .run_if(resource_exists::<ResumeRun>),
+            )
+            .add_systems(OnEnter(GameState::Win), delete_save)
+            .add_systems(OnEnter(GameState::GameOver), delete_save)
+            .add_systems(
+                Update,
+                save_on_hour
+                    .run_if(resource_exists::<SaveFile>.and_then(in_state(GameState::Running))),
+            )
+            .add_systems(
+                Last,
+                save_run.run_if(resource_exists::<SaveFile>.and_then(state_exists::<GameState>)),
+            );
+    }
+}
+
+/// Where the run is saved. Runs aren't saved without it, e.g. in
+/// simulations.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct SaveFile(pub PathBuf);
+
+/// Writes the run to the save file at the end of the frame.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SaveRunEvent;
+
+/// Save the next run is rebuilt from instead of starting a new one.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ResumeRun(pub RunSave);
+
+/// Everything needed to continue a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSave {
+    pub version: u32,
+    pub rng: RunRng,
+    pub cycles: u8,
+    pub player_last_sector: u8,
+    pub player: PlayerSave,
+    pub inventory: InventorySave,
+    pub sectors: Vec<SectorSave>,
+    pub enemies: Vec<EnemySave>,
+    pub chests: Vec<ChestSave>,
+    /// Elapsed cooldown of every spell, by spell id.
+    pub spell_cooldowns: Vec<(String, Duration)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub hp: f32,
+    pub max_hp: f32,
+    /// Angle on the clock, clockwise from 12.
+    pub angle: f32,
+}
+
+/// Content ids of every stack of the inventory, top first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventorySave {
+    pub active_items: Vec<String>,
+    pub backpack_items: Vec<String>,
+    pub active_spells: Vec<String>,
+    pub backpack_spells: Vec<String>,
+    pub backpack_sectors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectorSave {
+    pub position: u8,
+    pub sector: String,
+    pub timer_elapsed: Duration,
+    pub timer_paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnemySave {
+    pub id: String,
+    pub position: u8,
+    /// `None` for the last boss.
+    pub slot: Option<usize>,
+    pub hp: f32,
+    pub max_hp: f32,
+    pub damage: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChestSave {
+    pub id: String,
+    pub position: u8,
+    pub slot: usize,
+}
+
+/// Read first, so saves of other versions are reported as such instead
+/// of failing to parse.
+#[derive(Debug, Deserialize)]
+#[serde(rename = "RunSave")]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ron::error::SpannedError),
+    #[error(transparent)]
+    Write(#[from] ron::Error),
+    #[error("save version {0} is not supported, expected {SAVE_VERSION}")]
+    Version(u32),
+}
+
+impl RunSave {
+    pub fn load(path: &Path) -> Result<Self, SaveError> {
+        let text = std::fs::read_to_string(path)?;
+        let header = ron::from_str::<SaveHeader>(&text)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::Version(header.version));
+        }
+        Ok(ron::from_str::<Self>(&text)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+fn stack_ids<I: Copy + PartialEq, const N: usize>(
+    ids: &IdMap<I>,
+    stack: &Stack<I, N>,
+) -> Vec<String> {
+    stack
+        .iter()
+        .flatten()
+        .filter_map(|idx| ids.id(*idx).map(str::to_string))
+        .collect()
+}
+
+fn stack_from_ids<I: Copy, const N: usize>(ids: &IdMap<I>, saved: &[String]) -> Stack<I, N> {
+    let mut stack = Stack::new();
+    // Pushing puts things on top.
+    for id in saved.iter().rev() {
+        if let Some(idx) = ids.resolve("save", id) {
+            stack.push(idx);
+        }
+    }
+    stack
+}
+
+fn saved_health(hp: f32, max_hp: f32) -> Health {
+    let mut health = Health::new(max_hp);
+    health.take_damage(max_hp - hp);
+    health
+}
+
+/// Saves once the hour arrow moves.
+fn save_on_hour(
+    mut event_reader: EventReader<HourCompleteEvent>,
+    mut event_writer: EventWriter<SaveRunEvent>,
+) {
+    if event_reader.read().count() > 0 {
+        event_writer.send(SaveRunEvent);
+    }
+}
+
+/// Also saves when the game is closed mid-run.
+fn save_run(
+    save_file: Res<SaveFile>,
+    game_state: Res<State<GameState>>,
+    content_ids: Res<ContentIds>,
+    spells: Res<Spells>,
+    inventory: Res<Inventory>,
+    player_progress: Res<PlayerProgress>,
+    run_rng: Res<RunRng>,
+    player: Query<(&Transform, &Health), With<Player>>,
+    sectors: Query<(&SectorPosition, &SectorIdx, &SectorTimer)>,
+    enemies: Query<
+        (
+            &EnemyIdx,
+            &SectorPosition,
+            &Health,
+            &Damage,
+            Option<&SectorSlotEntity>,
+            Has<LastBossTag>,
+        ),
+        (With<Enemy>, Without<BattleEnemyDead>),
+    >,
+    chests: Query<(&ChestIdx, &SectorPosition, &SectorSlotEntity)>,
+    mut save_events: EventReader<SaveRunEvent>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    let requested = save_events.read().count() + exit_events.read().count();
+    if requested == 0 {
+        return;
+    }
+    // Finished runs have nothing to continue.
+    if matches!(
+        game_state.get(),
+        GameState::Preparing | GameState::Win | GameState::GameOver
+    ) {
+        return;
+    }
+
+    let Ok((player_transform, player_health)) = player.get_single() else {
+        return;
+    };
+    let position = player_transform.translation;
+
+    let save = RunSave {
+        version: SAVE_VERSION,
+        rng: run_rng.clone(),
+        cycles: player_progress.cycles,
+        player_last_sector: player_progress.player_last_sector,
+        player: PlayerSave {
+            hp: player_health.current(),
+            max_hp: player_health.max(),
+            angle: position.x.atan2(position.y).rem_euclid(2.0 * PI),
+        },
+        inventory: InventorySave {
+            active_items: stack_ids(&content_ids.items, &inventory.active_items),
+            backpack_items: stack_ids(&content_ids.items, &inventory.backpack_items),
+            active_spells: stack_ids(&content_ids.spells, &inventory.active_spells),
+            backpack_spells: stack_ids(&content_ids.spells, &inventory.backpack_spells),
+            backpack_sectors: stack_ids(&content_ids.sectors, &inventory.backpack_sectors),
+        },
+        sectors: sectors
+            .iter()
+            .filter_map(|(position, sector_idx, timer)| {
+                Some(SectorSave {
+                    position: position.0,
+                    sector: content_ids.sectors.id(*sector_idx)?.to_string(),
+                    timer_elapsed: timer.0.elapsed(),
+                    timer_paused: timer.0.paused(),
+                })
+            })
+            .collect(),
+        enemies: enemies
+            .iter()
+            .filter_map(|(enemy_idx, position, health, damage, slot, last_boss)| {
+                // Other enemies outside of slots can't be placed back.
+                let slot = match slot {
+                    Some(slot) => Some(slot.slot_position()),
+                    None if last_boss => None,
+                    None => return None,
+                };
+                Some(EnemySave {
+                    id: content_ids.enemies.id(*enemy_idx)?.to_string(),
+                    position: position.0,
+                    slot,
+                    hp: health.current(),
+                    max_hp: health.max(),
+                    damage: damage.0,
+                })
+            })
+            .collect(),
+        chests: chests
+            .iter()
+            .filter_map(|(chest_idx, position, slot)| {
+                Some(ChestSave {
+                    id: content_ids.chests.id(*chest_idx)?.to_string(),
+                    position: position.0,
+                    slot: slot.slot_position(),
+                })
+            })
+            .collect(),
+        spell_cooldowns: spells
+            .iter()
+            .filter_map(|(spell_idx, spell_info)| {
+                let id = content_ids.spells.id(spell_idx)?;
+                Some((id.to_string(), spell_info.cooldown.elapsed()))
+            })
+            .collect(),
+    };
+
+    match save.save(&save_file.0) {
+        Ok(()) => info!("run saved to {}", save_file.0.display()),
+        Err(e) => error!("can't write save {}: {e}", save_file.0.display()),
+    }
+}
+
+fn delete_save(save_file: Option<Res<SaveFile>>) {
+    let Some(save_file) = save_file else {
+        return;
+    };
+    match std::fs::remove_file(&save_file.0) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => error!("can't remove save {}: {e}", save_file.0.display()),
+    }
+}
+
+/// Runs on a fresh clock and player, so only what differs from a new run
+/// is put back. A battle or pickup in progress starts over.
+fn restore_run(
+    resume: Res<ResumeRun>,
+    content_ids: Res<ContentIds>,
+    enemies: Res<Enemies>,
+    sectors: Res<Sectors>,
+    chest_resources: Res<ChestResources>,
+    hp_bar_resources: Res<HpBarResources>,
+    mut spells: ResMut<Spells>,
+    mut inventory: ResMut<Inventory>,
+    mut commands: Commands,
+    mut player: Query<(&mut Transform, &mut InterpolatedTransform, &mut Health), With<Player>>,
+    mut sector_query: Query<(
+        Entity,
+        &SectorPosition,
+        &mut SectorIdx,
+        &mut SectorTimer,
+        &mut SectorSlots,
+        &mut Handle<ColorMaterial>,
+    )>,
+    mut backgrounds: Query<(&SectorPosition, &mut Handle<Image>)>,
+    mut inventory_update_event: EventWriter<InventoryUpdateEvent>,
+) {
+    let save = &resume.0;
+    commands.remove_resource::<ResumeRun>();
+    commands.insert_resource(save.rng.clone());
+    commands.insert_resource(PlayerProgress {
+        cycles: save.cycles,
+        player_last_sector: save.player_last_sector,
+    });
+
+    *inventory = Inventory {
+        active_items: stack_from_ids(&content_ids.items, &save.inventory.active_items),
+        backpack_items: stack_from_ids(&content_ids.items, &save.inventory.backpack_items),
+        active_spells: stack_from_ids(&content_ids.spells, &save.inventory.active_spells),
+        backpack_spells: stack_from_ids(&content_ids.spells, &save.inventory.backpack_spells),
+        backpack_sectors: stack_from_ids(&content_ids.sectors, &save.inventory.backpack_sectors),
+    };
+    inventory_update_event.send(InventoryUpdateEvent);
+
+    for (id, elapsed) in save.spell_cooldowns.iter() {
+        if let Some(spell_idx) = content_ids.spells.resolve("save", id) {
+            spells[spell_idx].cooldown.set_elapsed(*elapsed);
+        }
+    }
+
+    if let Ok((mut transform, mut interpolated, mut health)) = player.get_single_mut() {
+        let rotation = Quat::from_rotation_z(-save.player.angle);
+        transform.translation = rotation * Vec3::new(0.0, PLAYER_RING_RADIUS, Z_PLAYER);
+        transform.rotation = rotation;
+        *interpolated = InterpolatedTransform::new(*transform);
+        *health = saved_health(save.player.hp, save.player.max_hp);
+    }
+
+    for (entity, position, mut sector_idx, mut timer, mut slots, mut material) in
+        sector_query.iter_mut()
+    {
+        if let Some(sector_save) = save.sectors.iter().find(|s| s.position == position.0) {
+            if let Some(idx) = content_ids.sectors.resolve("save", &sector_save.sector) {
+                *sector_idx = idx;
+                *material = sectors[idx].material.clone();
+                for (bg_position, mut background) in backgrounds.iter_mut() {
+                    if bg_position == position {
+                        *background = sectors[idx].background.clone();
+                    }
+                }
+            }
+            timer.0.set_elapsed(sector_save.timer_elapsed);
+            if sector_save.timer_paused {
+                timer.0.pause();
+            } else {
+                timer.0.unpause();
+            }
+        }
+
+        for enemy_save in save.enemies.iter() {
+            let Some(slot) = enemy_save.slot else {
+                continue;
+            };
+            if enemy_save.position != position.0 {
+                continue;
+            }
+            if SECTOR_THINGS <= slot {
+                warn!("save has enemy '{}' in missing slot {slot}", enemy_save.id);
+                continue;
+            }
+            let Some(enemy_idx) = content_ids.enemies.resolve("save", &enemy_save.id) else {
+                continue;
+            };
+            spawn_sector_thing(
+                &mut commands,
+                enemies.as_ref(),
+                chest_resources.as_ref(),
+                hp_bar_resources.as_ref(),
+                1.0,
+                (entity, *position),
+                &mut slots,
+                slot,
+                SectorSpawn::Enemy(enemy_idx),
+            )
+            .insert((
+                saved_health(enemy_save.hp, enemy_save.max_hp),
+                Damage(enemy_save.damage),
+            ));
+        }
+
+        for chest_save in save.chests.iter() {
+            if chest_save.position != position.0 {
+                continue;
+            }
+            if SECTOR_THINGS <= chest_save.slot {
+                warn!(
+                    "save has chest '{}' in missing slot {}",
+                    chest_save.id, chest_save.slot
+                );
+                continue;
+            }
+            let Some(chest_idx) = content_ids.chests.resolve("save", &chest_save.id) else {
+                continue;
+            };
+            spawn_sector_thing(
+                &mut commands,
+                enemies.as_ref(),
+                chest_resources.as_ref(),
+                hp_bar_resources.as_ref(),
+                1.0,
+                (entity, *position),
+                &mut slots,
+                chest_save.slot,
+                SectorSpawn::Chest(chest_idx),
+            );
+        }
+    }
+
+    for boss_save in save.enemies.iter().filter(|e| e.slot.is_none()) {
+        let Some(boss_idx) = content_ids.enemies.resolve("save", &boss_save.id) else {
+            continue;
+        };
+        spawn_last_boss(
+            &mut commands,
+            enemies.as_ref(),
+            boss_idx,
+            hp_bar_resources.as_ref(),
+        )
+        .insert((
+            saved_health(boss_save.hp, boss_save.max_hp),
+            Damage(boss_save.damage),
+        ));
+    }
+
+    info!("run resumed at hour {}", save.cycles);
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Settings file on native, relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// `localStorage` key of the settings on wasm.
+#[cfg(target_arch = "wasm32")]
+pub const SETTINGS_KEY: &str = "settings";
+
+pub struct GameSettingsPlugin;
+
+impl Plugin for GameSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, prepare_settings)
+            .add_systems(Last, save_settings.run_if(resource_changed::<Settings>));
+    }
+}
+
+/// Player options kept between launches. Missing fields take their
+/// default, so options can be added without breaking older files.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+/// Volume controls of the settings menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeChannel {
+    pub const ALL: [Self; 3] = [Self::Master, Self::Music, Self::Sfx];
+}
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ron::error::SpannedError),
+    #[error(transparent)]
+    Write(#[from] ron::Error),
+    #[error("local storage is not available")]
+    Storage,
+}
+
+impl Settings {
+    /// Defaults are used if nothing was saved yet.
+    pub fn load() -> Result<Self, SettingsError> {
+        let Some(text) = read_settings()? else {
+            return Ok(Self::default());
+        };
+        let mut settings = ron::from_str::<Self>(&text)?;
+        // The file may be edited by hand.
+        for channel in VolumeChannel::ALL {
+            settings.set_volume(channel, settings.volume(channel));
+        }
+        Ok(settings)
+    }
+
+    pub fn volume(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master_volume,
+            VolumeChannel::Music => self.music_volume,
+            VolumeChannel::Sfx => self.sfx_volume,
+        }
+    }
+
+    /// Volumes are kept in `0.0..=1.0`.
+    pub fn set_volume(&mut self, channel: VolumeChannel, volume: f32) {
+        let volume = if volume.is_nan() {
+            0.0
+        } else {
+            volume.clamp(0.0, 1.0)
+        };
+        match channel {
+            VolumeChannel::Master => self.master_volume = volume,
+            VolumeChannel::Music => self.music_volume = volume,
+            VolumeChannel::Sfx => self.sfx_volume = volume,
+        }
+    }
+
+    /// Volume music is played at.
+    pub fn music(&self) -> f32 {
+        self.effective(self.music_volume)
+    }
+
+    /// Volume sound effects are played at.
+    pub fn sfx(&self) -> f32 {
+        self.effective(self.sfx_volume)
+    }
+
+    fn effective(&self, volume: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * volume
+        }
+    }
+
+    pub fn save(&self) -> Result<(), SettingsError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        write_settings(&text)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_settings() -> Result<Option<String>, SettingsError> {
+    match std::fs::read_to_string(SETTINGS_FILE) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_settings(text: &str) -> Result<(), SettingsError> {
+    std::fs::write(SETTINGS_FILE, text)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, SettingsError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or(SettingsError::Storage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_settings() -> Result<Option<String>, SettingsError> {
+    local_storage()?
+        .get_item(SETTINGS_KEY)
+        .map_err(|_| SettingsError::Storage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_settings(text: &str) -> Result<(), SettingsError> {
+    local_storage()?
+        .set_item(SETTINGS_KEY, text)
+        .map_err(|_| SettingsError::Storage)
+}
+
+fn prepare_settings(mut commands: Commands) {
+    let settings = Settings::load().unwrap_or_else(|e| {
+        error!("can't load settings, using defaults: {e}");
+        Settings::default()
+    });
+    commands.insert_resource(settings);
+}
+
+fn save_settings(settings: Res<Settings>) {
+    // Nothing to write right after loading.
+    if settings.is_added() {
+        return;
+    }
+    if let Err(e) = settings.save() {
+        error!("can't save settings: {e}");
+    }
+}
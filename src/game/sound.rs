@@ -1,53 +1,290 @@
 use bevy::{
     audio::{PlaybackMode, Volume},
     prelude::*,
+    utils::HashMap,
+};
+use rand::Rng;
+
+use super::{
+    circle_sectors::{HourCompleteEvent, SectorPlacedEvent},
+    settings::Settings,
+    GameState,
 };
 
 pub struct SoundPlugin;
 
 impl Plugin for SoundPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, prepare_sounds);
+        app.add_event::<SoundCueEvent>()
+            .add_systems(Startup, prepare_sounds)
+            .add_systems(OnEnter(GameState::Win), cue_win)
+            .add_systems(OnEnter(GameState::GameOver), cue_game_over)
+            .add_systems(
+                Update,
+                (
+                    update_volumes.run_if(resource_changed::<Settings>),
+                    (cue_sector_placed, cue_hour_complete).run_if(state_exists::<GameState>),
+                    play_sound_cues,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Everything in the game that makes a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    PlayerAttack,
+    EnemyAttack,
+    BossAttack,
+    ChestOpen,
+    SpellCast,
+    Heal,
+    ItemEquip,
+    SectorPlaced,
+    HourComplete,
+    Win,
+    GameOver,
+}
+
+/// Volume setting a cue follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundChannel {
+    Sfx,
+    Music,
+}
+
+/// Plays the sound of the cue on its channel.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SoundCueEvent(pub SoundCue);
+
+/// How a cue is played.
+#[derive(Debug, Clone)]
+pub struct CueSound {
+    pub source: Handle<AudioSource>,
+    pub channel: SoundChannel,
+    /// Multiplier of the channel volume.
+    pub volume: f32,
+    /// Playback speed is picked in `1.0 ± pitch_variation`.
+    pub pitch_variation: f32,
+    /// New cues are dropped while this many are playing.
+    pub max_instances: usize,
+    /// Music is turned down while it plays.
+    pub ducks_music: bool,
+}
+
+impl CueSound {
+    fn new(source: Handle<AudioSource>) -> Self {
+        Self {
+            source,
+            channel: SoundChannel::Sfx,
+            volume: 1.0,
+            pitch_variation: 0.0,
+            max_instances: 4,
+            ducks_music: false,
+        }
+    }
+
+    fn channel(mut self, channel: SoundChannel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    fn volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    fn pitch_variation(mut self, pitch_variation: f32) -> Self {
+        self.pitch_variation = pitch_variation;
+        self
+    }
+
+    fn max_instances(mut self, max_instances: usize) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    fn ducks_music(mut self) -> Self {
+        self.ducks_music = true;
+        self
     }
 }
 
 #[derive(Resource, Debug)]
 pub struct SoundResources {
-    pub player_attack: Handle<AudioSource>,
-    pub enemy_attack: Handle<AudioSource>,
-    pub boss_attack: Handle<AudioSource>,
-    pub chest_open: Handle<AudioSource>,
-    pub volume: Volume,
+    pub cues: HashMap<SoundCue, CueSound>,
+    /// Volume of the SFX channel.
+    pub sfx_volume: Volume,
+    /// Volume of the music channel.
+    pub music_volume: Volume,
 }
 
-#[derive(Component)]
-pub struct BackgroundMusic;
+/// Sound effect the music is ducked under.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DucksMusic;
+
+/// Sound playing for a cue, counted against its `max_instances`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PlayingCue(SoundCue);
 
-fn prepare_sounds(asset_server: Res<AssetServer>, mut commands: Commands) {
+fn prepare_sounds(asset_server: Res<AssetServer>, settings: Res<Settings>, mut commands: Commands) {
     let player_attack = asset_server.load("sounds/alex_attack.ogg");
+    let player_cast = asset_server.load("sounds/alex_cast.ogg");
     let enemy_attack = asset_server.load("sounds/enemy_attack.ogg");
     let boss_attack = asset_server.load("sounds/boss_attack.ogg");
     let chest_open = asset_server.load("sounds/chest_open.ogg");
-    let background = asset_server.load("sounds/background.ogg");
+    let stinger_win = asset_server.load("sounds/stinger_win.ogg");
+    let stinger_game_over = asset_server.load("sounds/stinger_game_over.ogg");
+
+    let cues = HashMap::from_iter([
+        (
+            SoundCue::PlayerAttack,
+            CueSound::new(player_attack).pitch_variation(0.1),
+        ),
+        (
+            SoundCue::EnemyAttack,
+            CueSound::new(enemy_attack.clone()).pitch_variation(0.1),
+        ),
+        (
+            SoundCue::BossAttack,
+            CueSound::new(boss_attack)
+                .pitch_variation(0.05)
+                .ducks_music(),
+        ),
+        (
+            SoundCue::ChestOpen,
+            CueSound::new(chest_open.clone()).ducks_music(),
+        ),
+        (
+            SoundCue::SpellCast,
+            CueSound::new(player_cast.clone()).pitch_variation(0.1),
+        ),
+        (
+            SoundCue::Heal,
+            CueSound::new(player_cast.clone())
+                .volume(0.8)
+                .pitch_variation(0.05)
+                .max_instances(1)
+                .ducks_music(),
+        ),
+        (
+            SoundCue::ItemEquip,
+            CueSound::new(chest_open)
+                .volume(0.5)
+                .pitch_variation(0.15)
+                .max_instances(1),
+        ),
+        (
+            SoundCue::SectorPlaced,
+            CueSound::new(enemy_attack).volume(0.6).max_instances(1),
+        ),
+        (
+            SoundCue::HourComplete,
+            CueSound::new(player_cast)
+                .volume(0.6)
+                .max_instances(1)
+                .ducks_music(),
+        ),
+        // The music director fades the tracks out after the run ends.
+        (
+            SoundCue::Win,
+            CueSound::new(stinger_win)
+                .channel(SoundChannel::Music)
+                .max_instances(1),
+        ),
+        (
+            SoundCue::GameOver,
+            CueSound::new(stinger_game_over)
+                .channel(SoundChannel::Music)
+                .max_instances(1),
+        ),
+    ]);
 
     commands.insert_resource(SoundResources {
-        player_attack,
-        enemy_attack,
-        boss_attack,
-        chest_open,
-        volume: Volume::new(1.0),
+        cues,
+        sfx_volume: Volume::new(settings.sfx()),
+        music_volume: Volume::new(settings.music()),
     });
+}
+
+fn update_volumes(settings: Res<Settings>, mut sounds: ResMut<SoundResources>) {
+    sounds.sfx_volume = Volume::new(settings.sfx());
+    sounds.music_volume = Volume::new(settings.music());
+}
+
+fn cue_win(mut event_writer: EventWriter<SoundCueEvent>) {
+    event_writer.send(SoundCueEvent(SoundCue::Win));
+}
+
+fn cue_game_over(mut event_writer: EventWriter<SoundCueEvent>) {
+    event_writer.send(SoundCueEvent(SoundCue::GameOver));
+}
 
-    // Background music
-    commands.spawn((
-        AudioBundle {
-            source: background,
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Loop,
-                volume: Volume::new(0.5),
-                ..Default::default()
+fn cue_sector_placed(
+    mut event_reader: EventReader<SectorPlacedEvent>,
+    mut event_writer: EventWriter<SoundCueEvent>,
+) {
+    for _ in event_reader.read() {
+        event_writer.send(SoundCueEvent(SoundCue::SectorPlaced));
+    }
+}
+
+fn cue_hour_complete(
+    mut event_reader: EventReader<HourCompleteEvent>,
+    mut event_writer: EventWriter<SoundCueEvent>,
+) {
+    for _ in event_reader.read() {
+        event_writer.send(SoundCueEvent(SoundCue::HourComplete));
+    }
+}
+
+/// Pitch is only for variety, so it doesn't take from the run randomness.
+fn play_sound_cues(
+    sounds: Res<SoundResources>,
+    playing: Query<&PlayingCue>,
+    mut commands: Commands,
+    mut event_reader: EventReader<SoundCueEvent>,
+) {
+    let mut instances = HashMap::<SoundCue, usize>::default();
+    for playing_cue in playing.iter() {
+        *instances.entry(playing_cue.0).or_default() += 1;
+    }
+
+    let mut rng = rand::thread_rng();
+    for e in event_reader.read() {
+        let Some(cue_sound) = sounds.cues.get(&e.0) else {
+            continue;
+        };
+        let count = instances.entry(e.0).or_default();
+        if *count >= cue_sound.max_instances {
+            continue;
+        }
+        *count += 1;
+
+        let speed = if cue_sound.pitch_variation > 0.0 {
+            1.0 + rng.gen_range(-cue_sound.pitch_variation..=cue_sound.pitch_variation)
+        } else {
+            1.0
+        };
+        let channel_volume = match cue_sound.channel {
+            SoundChannel::Sfx => sounds.sfx_volume,
+            SoundChannel::Music => sounds.music_volume,
+        };
+        let mut c = commands.spawn((
+            AudioBundle {
+                source: cue_sound.source.clone(),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new(channel_volume.get() * cue_sound.volume),
+                    speed,
+                    ..Default::default()
+                },
             },
-        },
-        BackgroundMusic,
-    ));
+            PlayingCue(e.0),
+        ));
+        if cue_sound.ducks_music {
+            c.insert(DucksMusic);
+        }
+    }
 }
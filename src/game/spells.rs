@@ -2,31 +2,54 @@ use std::ops::{Index, IndexMut};
 
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use super::{
-    animation::{DAMAGE_COLOR_FIRE_PUNCH, DAMAGE_COLOR_KEYBOARD, DAMAGE_COLOR_MARKER},
+    data::{
+        build_content_ids, srgb_u8, CheckContent, ContentAsset, ContentHandle, ContentLoadedEvent,
+        RonAssetLoader,
+    },
     enemy::{BattleEnemy, DamageEnemyEvent},
-    Damage, Defense, GameState, Health, Player,
+    player::DamagePlayerEvent,
+    rng::RunRng,
+    sound::{SoundCue, SoundCueEvent},
+    stats::{DamageType, Stats},
+    status_effects::{ApplyStatusEffectEvent, StatusEffectData},
+    GameState, GameplaySet, Health, Player, Shield,
 };
 
+/// Spell catalog, relative to the assets folder.
+pub const SPELLS_DATA: &str = "data/spells.ron";
+
 pub struct SpellsPlugin;
 
 impl Plugin for SpellsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<CastSpellEvent>()
+            .add_event::<EnemyCastSpellEvent>()
+            .init_asset::<SpellsAsset>()
+            .register_asset_loader(RonAssetLoader::<SpellsAsset>::new(&["spells.ron"]))
             .add_systems(Startup, prepare_spells)
-            .add_systems(Update, cooldown_spells.run_if(state_exists::<GameState>))
+            .add_systems(Update, build_spells.after(build_content_ids))
+            .add_systems(
+                FixedUpdate,
+                cooldown_spells
+                    .run_if(state_exists::<GameState>)
+                    .in_set(GameplaySet::Tick),
+            )
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     cast_spell,
-                    process_damage_spell,
-                    process_heal_spell,
-                    process_player_attack_up_spell,
-                    process_player_defence_up_spell,
-                    process_enemy_denfense_down_spell,
+                    (
+                        process_damage_spell,
+                        process_heal_spell,
+                        process_shield_spell,
+                    ),
                 )
-                    .run_if(in_state(GameState::Battle)),
+                    .chain()
+                    .run_if(in_state(GameState::Battle))
+                    .in_set(GameplaySet::Attack),
             );
     }
 }
@@ -37,87 +60,137 @@ pub struct SpellIdx(pub usize);
 #[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CastSpellEvent(pub SpellIdx);
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Spell cast by an enemy. The enemy heals and shields itself and damages
+/// the player.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnemyCastSpellEvent {
+    pub caster: Entity,
+    pub spell: SpellIdx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct DamageSpellInfo {
     strikes: u32,
     delta_time: f32,
     damage: f32,
+    #[serde(with = "srgb_u8")]
     color: Color,
     chance: f32,
+    #[serde(default)]
+    damage_type: DamageType,
 }
 
 #[derive(Component, Debug, Clone)]
 pub struct DamageSpell {
+    caster: Entity,
     timer: Timer,
     remaining_strikes: u32,
     damage: f32,
     color: Color,
     chance: f32,
+    damage_type: DamageType,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct HealSpellInfo {
     heal: f32,
 }
 
 #[derive(Component, Debug, Clone)]
 pub struct HealSpell {
+    target: Entity,
     heal: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct PlayerAttackUpSpellInfo {
-    duration: f32,
-    attack: f32,
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShieldSpellInfo {
+    shield: f32,
 }
 
 #[derive(Component, Debug, Clone)]
-pub struct PlayerAttackUpSpell {
-    timer: Timer,
-    active: bool,
-    attack: f32,
+pub struct ShieldSpell {
+    target: Entity,
+    shield: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct PlayerDefenseUpSpellInfo {
-    duration: f32,
-    defense: f32,
+/// Entity a status effect lands on, seen from whoever casts it: the
+/// player casting a spell or wearing an item, or the enemy casting a spell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EffectTarget {
+    Caster,
+    Opponent,
 }
 
-#[derive(Component, Debug, Clone)]
-pub struct PlayerDefenseUpSpell {
-    timer: Timer,
-    active: bool,
-    defense: f32,
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EffectSpellInfo {
+    target: EffectTarget,
+    effect: StatusEffectData,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Spell {
+    Damage(DamageSpellInfo),
+    Heal(HealSpellInfo),
+    Shield(ShieldSpellInfo),
+    Effect(EffectSpellInfo),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct EnemyDefenseDownSpellInfo {
-    duration: f32,
-    defense: f32,
+/// Single entry of the `assets/data/spells.ron` file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellData {
+    pub id: String,
+    pub description: String,
+    pub image: String,
+    /// Cooldown in seconds.
+    pub cooldown: f32,
+    pub spell: Spell,
 }
 
-#[derive(Component, Debug, Clone)]
-pub struct EnemyDefenseDownSpell {
-    timer: Timer,
-    active: bool,
-    defense: f32,
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct SpellsAsset {
+    pub spells: Vec<SpellData>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Spell {
-    Damage(DamageSpellInfo),
-    Heal(HealSpellInfo),
-    PlayerAttackUp(PlayerAttackUpSpellInfo),
-    PlayerDefenseUp(PlayerDefenseUpSpellInfo),
-    EnemyDefenseDown(EnemyDefenseDownSpellInfo),
+impl CheckContent for SpellsAsset {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for spell_data in self.spells.iter() {
+            let id = &spell_data.id;
+            if !(0.0..f32::INFINITY).contains(&spell_data.cooldown) {
+                problems.push(format!("{id} has invalid cooldown {}", spell_data.cooldown));
+            }
+            match spell_data.spell {
+                Spell::Damage(damage_spell_info) => {
+                    if !(0.0..=1.0).contains(&damage_spell_info.chance) {
+                        problems.push(format!(
+                            "{id} has chance {} outside of [0, 1]",
+                            damage_spell_info.chance
+                        ));
+                    }
+                    if damage_spell_info.strikes == 0 {
+                        problems.push(format!("{id} has no strikes"));
+                    }
+                    if !(0.0..f32::INFINITY).contains(&damage_spell_info.delta_time) {
+                        problems.push(format!(
+                            "{id} has invalid delta_time {}",
+                            damage_spell_info.delta_time
+                        ));
+                    }
+                }
+                Spell::Effect(effect_spell_info) => {
+                    effect_spell_info.effect.check(id, &mut problems);
+                }
+                Spell::Heal(_) | Spell::Shield(_) => {}
+            }
+        }
+        problems
+    }
 }
 
 #[derive(Debug)]
 pub struct SpellInfo {
-    pub description: &'static str,
+    pub description: String,
     pub image: Handle<Image>,
-    pub drop_rate: f32,
     pub cooldown: Timer,
     pub spell: Spell,
 }
@@ -125,6 +198,15 @@ pub struct SpellInfo {
 #[derive(Resource, Debug)]
 pub struct Spells(Vec<SpellInfo>);
 
+impl Spells {
+    pub fn iter(&self) -> impl Iterator<Item = (SpellIdx, &SpellInfo)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, info)| (SpellIdx(i), info))
+    }
+}
+
 impl Index<SpellIdx> for Spells {
     type Output = SpellInfo;
     fn index(&self, index: SpellIdx) -> &Self::Output {
@@ -139,94 +221,41 @@ impl IndexMut<SpellIdx> for Spells {
 }
 
 fn prepare_spells(asset_server: Res<AssetServer>, mut commands: Commands) {
-    let mut spells = Spells(vec![]);
-
-    // 0 marker
-    spells.0.push(SpellInfo {
-        description: "Use coworker's face as a white board. Draw 2 lines, 5 damage each.",
-        image: asset_server.load("spells/spell_marker.png"),
-        drop_rate: 0.8,
-        cooldown: Timer::from_seconds(2.0, TimerMode::Once),
-        spell: Spell::Damage(DamageSpellInfo {
-            strikes: 2,
-            delta_time: 0.1,
-            damage: 5.0,
-            color: DAMAGE_COLOR_MARKER,
-            chance: 1.0,
-        }),
-    });
-    // 1 keyboard
-    spells.0.push(SpellInfo {
-        description: "Print monthly report with coworker's face. Deals 50 damage.",
-        image: asset_server.load("spells/spell_keyboard.png"),
-        drop_rate: 0.7,
-        cooldown: Timer::from_seconds(5.0, TimerMode::Once),
-        spell: Spell::Damage(DamageSpellInfo {
-            strikes: 1,
-            delta_time: 0.0,
-            damage: 50.0,
-            color: DAMAGE_COLOR_KEYBOARD,
-            chance: 1.0,
-        }),
-    });
-    // 2 punch
-    spells.0.push(SpellInfo {
-        description: "50% chance to layoff coworker and deal 100 damage",
-        image: asset_server.load("spells/spell_punch.png"),
-        drop_rate: 0.2,
-        cooldown: Timer::from_seconds(15.0, TimerMode::Once),
-        spell: Spell::Damage(DamageSpellInfo {
-            strikes: 1,
-            delta_time: 0.0,
-            damage: 100.0,
-            color: DAMAGE_COLOR_FIRE_PUNCH,
-            chance: 0.5,
-        }),
-    });
-    // 3 lunchbox
-    spells.0.push(SpellInfo {
-        description: "Delicious lunch. Restores 30 hp.",
-        image: asset_server.load("spells/spell_lunchbox.png"),
-        drop_rate: 0.2,
-        cooldown: Timer::from_seconds(10.0, TimerMode::Once),
-        spell: Spell::Heal(HealSpellInfo { heal: 30.0 }),
-    });
-    // 4 excel
-    spells.0.push(SpellInfo {
-        description: "Excels player damage by 10 for 10 seconds.",
-        image: asset_server.load("spells/spell_excel.png"),
-        drop_rate: 0.3,
-        cooldown: Timer::from_seconds(12.0, TimerMode::Once),
-        spell: Spell::PlayerAttackUp(PlayerAttackUpSpellInfo {
-            duration: 10.0,
-            attack: 10.0,
-        }),
-    });
-    // 5 stand up
-    spells.0.push(SpellInfo {
-        description: "Attending standup raises defence by 10% for 10 seconds.",
-        image: asset_server.load("spells/spell_standup.png"),
-        drop_rate: 0.3,
-        cooldown: Timer::from_seconds(12.0, TimerMode::Once),
-        spell: Spell::PlayerDefenseUp(PlayerDefenseUpSpellInfo {
-            duration: 10.0,
-            defense: 0.1,
-        }),
-    });
-    // 6 powerpoint
-    spells.0.push(SpellInfo {
-        description:
-            "Present future plans to coworker. Lowers coworker defence by 10% for 10 seconds.",
-        image: asset_server.load("spells/spell_powerpoint.png"),
-        drop_rate: 0.3,
-        cooldown: Timer::from_seconds(12.0, TimerMode::Once),
-        spell: Spell::EnemyDefenseDown(EnemyDefenseDownSpellInfo {
-            duration: 10.0,
-            defense: 0.1,
-        }),
-    });
-
-    commands.insert_resource(spells);
+    commands.insert_resource(ContentHandle::<SpellsAsset>(asset_server.load(SPELLS_DATA)));
+    // Filled by `build_spells` once the data file is loaded.
+    commands.insert_resource(Spells(vec![]));
+}
+
+fn build_spells(
+    asset_server: Res<AssetServer>,
+    spells_asset: ContentAsset<SpellsAsset>,
+    mut spells: ResMut<Spells>,
+    mut event_reader: EventReader<ContentLoadedEvent>,
+) {
+    for _ in event_reader.read() {
+        let Some(spells_asset) = spells_asset.get() else {
+            continue;
+        };
+
+        spells.0 = spells_asset
+            .spells
+            .iter()
+            .enumerate()
+            .map(|(i, spell_data)| {
+                let mut cooldown = Timer::from_seconds(spell_data.cooldown, TimerMode::Once);
+                // Keep cooldowns running on reload.
+                if let Some(old) = spells.0.get(i) {
+                    cooldown.set_elapsed(old.cooldown.elapsed());
+                }
+                SpellInfo {
+                    description: spell_data.description.clone(),
+                    image: asset_server.load(spell_data.image.clone()),
+                    cooldown,
+                    spell: spell_data.spell,
+                }
+            })
+            .collect();
+    }
 }
 
 fn cooldown_spells(time: Res<Time>, mut spells: ResMut<Spells>) {
@@ -239,48 +268,72 @@ fn cast_spell(
     mut commands: Commands,
     mut spells: ResMut<Spells>,
     mut event_reader: EventReader<CastSpellEvent>,
+    mut enemy_event_reader: EventReader<EnemyCastSpellEvent>,
+    player: Query<Entity, With<Player>>,
+    enemy: Query<Entity, With<BattleEnemy>>,
+    mut effect_event: EventWriter<ApplyStatusEffectEvent>,
+    mut sound_event: EventWriter<SoundCueEvent>,
 ) {
+    let Ok(player_entity) = player.get_single() else {
+        return;
+    };
+    let enemy_entity = enemy.get_single().ok();
+
+    let mut casts = vec![];
     for e in event_reader.read() {
-        let spell_info = &mut spells.0[e.0 .0];
+        let spell_info = &mut spells[e.0];
         if !spell_info.cooldown.finished() {
             continue;
         } else {
             spell_info.cooldown.reset();
         }
-        match spell_info.spell {
+        casts.push((spell_info.spell, player_entity, enemy_entity));
+    }
+    // Enemies keep their own cooldowns.
+    for e in enemy_event_reader.read() {
+        casts.push((spells[e.spell].spell, e.caster, Some(player_entity)));
+    }
+
+    for (spell, caster, opponent) in casts {
+        sound_event.send(SoundCueEvent(match spell {
+            Spell::Heal(_) => SoundCue::Heal,
+            _ => SoundCue::SpellCast,
+        }));
+        match spell {
             Spell::Damage(damage_spell_info) => {
                 commands.spawn(DamageSpell {
+                    caster,
                     timer: Timer::from_seconds(damage_spell_info.delta_time, TimerMode::Repeating),
                     remaining_strikes: damage_spell_info.strikes,
                     damage: damage_spell_info.damage,
                     color: damage_spell_info.color,
                     chance: damage_spell_info.chance,
+                    damage_type: damage_spell_info.damage_type,
                 });
             }
             Spell::Heal(heal_spell_info) => {
                 commands.spawn(HealSpell {
+                    target: caster,
                     heal: heal_spell_info.heal,
                 });
             }
-            Spell::PlayerAttackUp(attack_up_spell_info) => {
-                commands.spawn(PlayerAttackUpSpell {
-                    timer: Timer::from_seconds(attack_up_spell_info.duration, TimerMode::Once),
-                    active: false,
-                    attack: attack_up_spell_info.attack,
+            Spell::Shield(shield_spell_info) => {
+                commands.spawn(ShieldSpell {
+                    target: caster,
+                    shield: shield_spell_info.shield,
                 });
             }
-            Spell::PlayerDefenseUp(defense_up_spell_info) => {
-                commands.spawn(PlayerDefenseUpSpell {
-                    timer: Timer::from_seconds(defense_up_spell_info.duration, TimerMode::Once),
-                    active: false,
-                    defense: defense_up_spell_info.defense,
-                });
-            }
-            Spell::EnemyDefenseDown(defense_down_spell_info) => {
-                commands.spawn(EnemyDefenseDownSpell {
-                    timer: Timer::from_seconds(defense_down_spell_info.duration, TimerMode::Once),
-                    active: false,
-                    defense: defense_down_spell_info.defense,
+            Spell::Effect(effect_spell_info) => {
+                let target = match effect_spell_info.target {
+                    EffectTarget::Caster => Some(caster),
+                    EffectTarget::Opponent => opponent,
+                };
+                let Some(target) = target else {
+                    continue;
+                };
+                effect_event.send(ApplyStatusEffectEvent {
+                    target,
+                    effect: effect_spell_info.effect,
                 });
             }
         }
@@ -290,17 +343,45 @@ fn cast_spell(
 fn process_damage_spell(
     time: Res<Time>,
     mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    player: Query<Entity, With<Player>>,
+    casters: Query<&Stats>,
     mut damage_spelll: Query<(Entity, &mut DamageSpell)>,
-    mut event_writer: EventWriter<DamageEnemyEvent>,
+    mut damage_enemy_event: EventWriter<DamageEnemyEvent>,
+    mut damage_player_event: EventWriter<DamagePlayerEvent>,
 ) {
+    let Ok(player_entity) = player.get_single() else {
+        return;
+    };
+
     for (entity, mut damage_spell) in damage_spelll.iter_mut() {
+        // The enemy casting it is gone.
+        let Ok(caster_stats) = casters.get(damage_spell.caster) else {
+            commands.get_entity(entity).unwrap().despawn_recursive();
+            continue;
+        };
+
         damage_spell.timer.tick(time.delta());
         if damage_spell.timer.finished() {
-            if rand::thread_rng().gen_bool(damage_spell.chance as f64) {
-                event_writer.send(DamageEnemyEvent {
-                    damage: damage_spell.damage,
-                    color: damage_spell.color,
-                });
+            if run_rng.combat.gen_bool(damage_spell.chance as f64) {
+                // Spells crit like the attacks of their caster.
+                let (damage, crit) =
+                    caster_stats.roll_crit(damage_spell.damage, &mut run_rng.combat);
+                if damage_spell.caster == player_entity {
+                    damage_enemy_event.send(DamageEnemyEvent {
+                        damage,
+                        color: damage_spell.color,
+                        damage_type: damage_spell.damage_type,
+                        crit,
+                    });
+                } else {
+                    damage_player_event.send(DamagePlayerEvent {
+                        damage,
+                        color: damage_spell.color,
+                        damage_type: damage_spell.damage_type,
+                        crit,
+                    });
+                }
             }
             damage_spell.remaining_strikes -= 1;
 
@@ -314,14 +395,12 @@ fn process_damage_spell(
 fn process_heal_spell(
     heals: Query<(Entity, &HealSpell)>,
     mut commands: Commands,
-    mut player: Query<&mut Health, With<Player>>,
+    mut healths: Query<&mut Health>,
 ) {
-    let Ok(mut player_health) = player.get_single_mut() else {
-        return;
-    };
-
     for (heal_entity, heal) in heals.iter() {
-        player_health.heal(heal.heal);
+        if let Ok(mut health) = healths.get_mut(heal.target) {
+            health.heal(heal.heal);
+        }
         commands
             .get_entity(heal_entity)
             .unwrap()
@@ -329,77 +408,18 @@ fn process_heal_spell(
     }
 }
 
-fn process_player_attack_up_spell(
-    time: Res<Time>,
+fn process_shield_spell(
+    shields: Query<(Entity, &ShieldSpell)>,
     mut commands: Commands,
-    mut player: Query<&mut Damage, With<Player>>,
-    mut player_attack_up: Query<(Entity, &mut PlayerAttackUpSpell)>,
+    mut targets: Query<&mut Shield>,
 ) {
-    let Ok(mut attack) = player.get_single_mut() else {
-        return;
-    };
-
-    for (entity, mut attack_up) in player_attack_up.iter_mut() {
-        if attack_up.active {
-            attack_up.timer.tick(time.delta());
-            if attack_up.timer.finished() {
-                attack.0 -= attack_up.attack;
-                commands.get_entity(entity).unwrap().despawn_recursive()
-            }
-        } else {
-            attack_up.active = true;
-            attack.0 += attack_up.attack;
-        }
-    }
-}
-
-fn process_player_defence_up_spell(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut player: Query<&mut Defense, With<Player>>,
-    mut player_defense_up: Query<(Entity, &mut PlayerDefenseUpSpell)>,
-) {
-    let Ok(mut defense) = player.get_single_mut() else {
-        return;
-    };
-
-    for (entity, mut defense_up) in player_defense_up.iter_mut() {
-        if defense_up.active {
-            defense_up.timer.tick(time.delta());
-            if defense_up.timer.finished() {
-                defense.0 -= defense_up.defense;
-                commands.get_entity(entity).unwrap().despawn_recursive()
-            }
-        } else {
-            defense_up.active = true;
-            defense.0 += defense_up.defense;
-        }
-    }
-}
-
-fn process_enemy_denfense_down_spell(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut enemy: Query<&mut Defense, With<BattleEnemy>>,
-    mut enemy_defense_down: Query<(Entity, &mut EnemyDefenseDownSpell)>,
-) {
-    let Ok(mut defense) = enemy.get_single_mut() else {
-        for (entity, _) in enemy_defense_down.iter() {
-            commands.get_entity(entity).unwrap().despawn_recursive()
-        }
-        return;
-    };
-
-    for (entity, mut defense_down) in enemy_defense_down.iter_mut() {
-        if defense_down.active {
-            defense_down.timer.tick(time.delta());
-            if defense_down.timer.finished() {
-                defense.0 += defense_down.defense;
-                commands.get_entity(entity).unwrap().despawn_recursive()
-            }
-        } else {
-            defense_down.active = true;
-            defense.0 -= defense_down.defense;
+    for (shield_entity, shield) in shields.iter() {
+        if let Ok(mut target_shield) = targets.get_mut(shield.target) {
+            target_shield.0 += shield.shield;
         }
+        commands
+            .get_entity(shield_entity)
+            .unwrap()
+            .despawn_recursive()
     }
 }
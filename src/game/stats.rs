@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    inventory::Inventory,
+    items::Items,
+    status_effects::{tick_status_effects, StatusEffect, StatusEffects},
+    Crit, Damage, Defense, GameState, GameplaySet, Player,
+};
+
+/// Highest defense anything can have, so hits always hurt.
+pub const MAX_DEFENSE: f32 = 0.9;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_stats
+                .after(tick_status_effects)
+                .run_if(state_exists::<GameState>)
+                .in_set(GameplaySet::Tick),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stat {
+    Damage,
+    Defense,
+    CritChance,
+    CritMultiplier,
+}
+
+impl Stat {
+    pub const ALL: [Self; 4] = [
+        Self::Damage,
+        Self::Defense,
+        Self::CritChance,
+        Self::CritMultiplier,
+    ];
+
+    /// Limits no modifier can go past.
+    fn limits(self) -> (f32, f32) {
+        match self {
+            Stat::Damage => (0.0, f32::INFINITY),
+            Stat::Defense => (f32::NEG_INFINITY, MAX_DEFENSE),
+            Stat::CritChance => (0.0, 1.0),
+            Stat::CritMultiplier => (1.0, f32::INFINITY),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Paperwork,
+    Caffeine,
+}
+
+/// Part of the damage of each type that is ignored. Negative values are
+/// weaknesses.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct Resistances(HashMap<DamageType, f32>);
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        self.0.get(&damage_type).copied().unwrap_or(0.0).min(1.0)
+    }
+}
+
+/// Applied in order: `(base + flat) * (1 + percent) * multiply`, then
+/// clamped to the highest `Min` and the lowest `Max`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ModifierKind {
+    Flat(f32),
+    /// Percents of the same stat add up, `0.1` is +10%.
+    Percent(f32),
+    Multiply(f32),
+    Min(f32),
+    Max(f32),
+}
+
+impl ModifierKind {
+    /// Same modifier applied `times` times.
+    pub fn scaled(self, times: f32) -> Self {
+        match self {
+            Self::Flat(value) => Self::Flat(value * times),
+            Self::Percent(value) => Self::Percent(value * times),
+            Self::Multiply(value) => Self::Multiply(value.powf(times)),
+            Self::Min(_) | Self::Max(_) => self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+}
+
+impl Modifier {
+    pub fn flat(stat: Stat, value: f32) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Flat(value),
+        }
+    }
+}
+
+/// Final stats of an entity from its `Damage`, `Defense` and `Crit`,
+/// items and status effects. Combat reads them from here.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    damage: f32,
+    defense: f32,
+    crit_chance: f32,
+    crit_multiplier: f32,
+}
+
+impl Stats {
+    pub fn get(&self, stat: Stat) -> f32 {
+        match stat {
+            Stat::Damage => self.damage,
+            Stat::Defense => self.defense,
+            Stat::CritChance => self.crit_chance,
+            Stat::CritMultiplier => self.crit_multiplier,
+        }
+    }
+
+    pub fn damage(&self) -> f32 {
+        self.damage
+    }
+
+    pub fn defense(&self) -> f32 {
+        self.defense
+    }
+
+    /// Rolls a crit on `damage`, returns the damage and whether it crit.
+    pub fn roll_crit(&self, damage: f32, rng: &mut impl Rng) -> (f32, bool) {
+        if rng.gen_bool(self.crit_chance as f64) {
+            (damage * self.crit_multiplier, true)
+        } else {
+            (damage, false)
+        }
+    }
+
+    fn set(&mut self, stat: Stat, value: f32) {
+        match stat {
+            Stat::Damage => self.damage = value,
+            Stat::Defense => self.defense = value,
+            Stat::CritChance => self.crit_chance = value,
+            Stat::CritMultiplier => self.crit_multiplier = value,
+        }
+    }
+
+    fn recompute(&mut self, base: Stats, modifiers: &[Modifier]) {
+        for stat in Stat::ALL {
+            self.set(stat, compute_stat(stat, base.get(stat), modifiers));
+        }
+    }
+}
+
+pub fn compute_stat(stat: Stat, base: f32, modifiers: &[Modifier]) -> f32 {
+    let (mut min, mut max) = stat.limits();
+    let mut flat = 0.0;
+    let mut percent = 0.0;
+    let mut multiply = 1.0;
+    for modifier in modifiers.iter().filter(|modifier| modifier.stat == stat) {
+        match modifier.kind {
+            ModifierKind::Flat(value) => flat += value,
+            ModifierKind::Percent(value) => percent += value,
+            ModifierKind::Multiply(value) => multiply *= value,
+            ModifierKind::Min(value) => min = min.max(value),
+            ModifierKind::Max(value) => max = max.min(value),
+        }
+    }
+    let value = (base + flat) * (1.0 + percent) * multiply;
+    // Not `clamp`, clashing limits from the data must not panic.
+    value.max(min).min(max)
+}
+
+/// Only recomputes when something the stats come from changed.
+fn update_stats(
+    items: Res<Items>,
+    inventory: Res<Inventory>,
+    status_effects: StatusEffects,
+    changed_effects: Query<(), Changed<StatusEffect>>,
+    mut removed_effects: RemovedComponents<StatusEffect>,
+    mut stats: Query<(
+        Entity,
+        Ref<Damage>,
+        Ref<Defense>,
+        Ref<Crit>,
+        &mut Stats,
+        Has<Player>,
+    )>,
+) {
+    let effects_removed = removed_effects.read().count() > 0;
+    let effects_changed = effects_removed || !changed_effects.is_empty();
+
+    let mut modifiers = vec![];
+    for (entity, damage, defense, crit, mut entity_stats, is_player) in stats.iter_mut() {
+        let changed = entity_stats.is_added()
+            || damage.is_changed()
+            || defense.is_changed()
+            || crit.is_changed()
+            || effects_changed
+            // Items change on hot reload too.
+            || (is_player && (inventory.is_changed() || items.is_changed()));
+        if !changed {
+            continue;
+        }
+
+        modifiers.clear();
+        if is_player {
+            for item_idx in inventory.active_items.iter().flatten() {
+                modifiers.extend(items[*item_idx].item.modifiers());
+            }
+        }
+        modifiers.extend(status_effects.modifiers(entity));
+        let base = Stats {
+            damage: damage.0,
+            defense: defense.0,
+            crit_chance: crit.chance,
+            crit_multiplier: crit.multiplier,
+        };
+        entity_stats.recompute(base, &modifiers);
+    }
+}
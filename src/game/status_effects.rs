@@ -0,0 +1,434 @@
+use std::mem::discriminant;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::GlobalState;
+
+use super::{
+    data::srgb_u8,
+    enemy::{BattleEnemy, DamageEnemyEvent, EnemyDeadEvent},
+    player::DamagePlayerEvent,
+    stats::{DamageType, Modifier, Stat},
+    AttackLandedEvent, GameState, GameplaySet, Player,
+};
+
+/// Crowd controls in a row before an entity with `DiminishingReturns`
+/// becomes immune.
+const DIMINISHING_RETURNS_IMMUNE: u32 = 3;
+/// Seconds without crowd control for diminishing returns to start over.
+const DIMINISHING_RETURNS_RESET: f32 = 15.0;
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        let playing = || {
+            in_state(GameState::Running)
+                .or_else(in_state(GameState::Battle).or_else(in_state(GameState::Pickup)))
+        };
+        app.add_event::<ApplyStatusEffectEvent>()
+            .add_event::<StatusEffectAppliedEvent>()
+            .add_event::<StatusEffectTickEvent>()
+            .add_systems(
+                FixedUpdate,
+                (apply_status_effects, tick_status_effects)
+                    .chain()
+                    .run_if(playing())
+                    .in_set(GameplaySet::Tick),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    damage_over_time.in_set(GameplaySet::Attack),
+                    use_charges.in_set(GameplaySet::Check),
+                    end_battle_effects.in_set(GameplaySet::Transition),
+                )
+                    .run_if(in_state(GameState::Battle)),
+            );
+    }
+}
+
+/// What an effect does to its target. Effects of the same kind on the
+/// same target follow the `Stacking` of the newer one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    /// Added to the damage of attacks, per stack.
+    Damage(f32),
+    /// Added to defense, per stack. Negative values lower it.
+    Defense(f32),
+    /// Any stat modifier. Modifiers of different stats or ops are
+    /// different kinds.
+    Stat(Modifier),
+    /// Attacks don't charge, and an attack in progress is cancelled.
+    Stun,
+    /// Cancels an attack in progress and starts its charge over.
+    Interrupt,
+    /// Damage per stack on every tick. Ends with the battle.
+    DamageOverTime {
+        damage: f32,
+        #[serde(with = "srgb_u8")]
+        color: Color,
+        #[serde(default)]
+        damage_type: DamageType,
+    },
+}
+
+impl StatusEffectKind {
+    fn same_as(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Stat(a), Self::Stat(b)) => {
+                a.stat == b.stat && discriminant(&a.kind) == discriminant(&b.kind)
+            }
+            _ => discriminant(self) == discriminant(other),
+        }
+    }
+
+    pub fn is_crowd_control(&self) -> bool {
+        matches!(self, Self::Stun | Self::Interrupt)
+    }
+
+    fn ends_with_battle(&self) -> bool {
+        matches!(self, Self::DamageOverTime { .. })
+    }
+}
+
+/// What happens when an effect is applied to a target that already has
+/// one of the same kind.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stacking {
+    /// The old effect starts over.
+    #[default]
+    Refresh,
+    /// The old effect starts over with one more stack.
+    Stack,
+    /// The new effect is dropped.
+    Ignore,
+}
+
+/// Status effect as written in the data files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffectData {
+    pub kind: StatusEffectKind,
+    /// Duration in seconds.
+    pub duration: f32,
+    #[serde(default)]
+    pub stacking: Stacking,
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: u32,
+    /// Seconds between `StatusEffectTickEvent`s, if the effect ticks.
+    #[serde(default)]
+    pub tick: Option<f32>,
+    /// Attacks of the target it lasts for. Ends when it runs out of
+    /// charges or its duration ends, whichever comes first.
+    #[serde(default)]
+    pub charges: Option<u32>,
+}
+
+impl StatusEffectData {
+    /// Reports values that would panic or hang the game.
+    pub fn check(&self, owner: &str, problems: &mut Vec<String>) {
+        if !(0.0..f32::INFINITY).contains(&self.duration) {
+            problems.push(format!(
+                "{owner} has a status effect with invalid duration {}",
+                self.duration
+            ));
+        }
+        if let Some(tick) = self
+            .tick
+            .filter(|tick| !(f32::MIN_POSITIVE..f32::INFINITY).contains(tick))
+        {
+            problems.push(format!(
+                "{owner} has a status effect with invalid tick {tick}"
+            ));
+        }
+    }
+}
+
+fn default_max_stacks() -> u32 {
+    1
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ApplyStatusEffectEvent {
+    pub target: Entity,
+    pub effect: StatusEffectData,
+}
+
+/// Sent when an effect lands, after stacking and diminishing returns.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffectAppliedEvent {
+    pub target: Entity,
+    pub kind: StatusEffectKind,
+}
+
+/// Sent on every tick of a ticking effect.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffectTickEvent {
+    pub target: Entity,
+    pub kind: StatusEffectKind,
+    pub stacks: u32,
+}
+
+/// Effect on another entity. Despawning it removes the effect, and it is
+/// despawned once it runs out or its target is gone.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct StatusEffect {
+    pub target: Entity,
+    pub kind: StatusEffectKind,
+    pub stacks: u32,
+    max_stacks: u32,
+    duration: Timer,
+    tick: Option<Timer>,
+    charges: Option<u32>,
+}
+
+impl StatusEffect {
+    pub fn modifier(&self) -> Option<Modifier> {
+        let stacks = self.stacks as f32;
+        match self.kind {
+            StatusEffectKind::Damage(damage) => Some(Modifier::flat(Stat::Damage, damage * stacks)),
+            StatusEffectKind::Defense(defense) => {
+                Some(Modifier::flat(Stat::Defense, defense * stacks))
+            }
+            StatusEffectKind::Stat(modifier) => Some(Modifier {
+                stat: modifier.stat,
+                kind: modifier.kind.scaled(stacks),
+            }),
+            StatusEffectKind::Stun
+            | StatusEffectKind::Interrupt
+            | StatusEffectKind::DamageOverTime { .. } => None,
+        }
+    }
+
+    fn new(target: Entity, data: &StatusEffectData) -> Self {
+        Self {
+            target,
+            kind: data.kind,
+            stacks: 1,
+            max_stacks: data.max_stacks.max(1),
+            duration: Timer::from_seconds(data.duration, TimerMode::Once),
+            tick: data
+                .tick
+                .map(|seconds| Timer::from_seconds(seconds, TimerMode::Repeating)),
+            charges: data.charges,
+        }
+    }
+}
+
+/// Crowd control on the entity lasts half as long every time, until it
+/// is immune. Starts over after a while without any.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct DiminishingReturns {
+    applied: u32,
+    reset: Timer,
+}
+
+impl Default for DiminishingReturns {
+    fn default() -> Self {
+        Self {
+            applied: 0,
+            reset: Timer::from_seconds(DIMINISHING_RETURNS_RESET, TimerMode::Once),
+        }
+    }
+}
+
+impl DiminishingReturns {
+    /// Duration multiplier of the next crowd control, `None` if immune.
+    fn next(&mut self) -> Option<f32> {
+        if DIMINISHING_RETURNS_IMMUNE <= self.applied {
+            return None;
+        }
+        let scale = 0.5_f32.powi(self.applied as i32);
+        self.applied += 1;
+        self.reset.reset();
+        Some(scale)
+    }
+}
+
+/// Current effects on entities.
+#[derive(SystemParam)]
+pub struct StatusEffects<'w, 's> {
+    effects: Query<'w, 's, &'static StatusEffect>,
+}
+
+impl StatusEffects<'_, '_> {
+    pub fn on(&self, target: Entity) -> impl Iterator<Item = &StatusEffect> {
+        self.effects
+            .iter()
+            .filter(move |effect| effect.target == target)
+    }
+
+    pub fn is_stunned(&self, target: Entity) -> bool {
+        self.on(target)
+            .any(|effect| effect.kind == StatusEffectKind::Stun)
+    }
+
+    /// Stat modifiers of the effects on the target.
+    pub fn modifiers(&self, target: Entity) -> impl Iterator<Item = Modifier> + '_ {
+        self.on(target).filter_map(|effect| effect.modifier())
+    }
+}
+
+pub fn apply_status_effects(
+    mut commands: Commands,
+    mut effects: Query<&mut StatusEffect>,
+    mut diminishing_returns: Query<&mut DiminishingReturns>,
+    mut event_reader: EventReader<ApplyStatusEffectEvent>,
+    mut event_writer: EventWriter<StatusEffectAppliedEvent>,
+) {
+    // Spawned at the end, events of the same step merge into them meanwhile.
+    let mut new_effects = Vec::<StatusEffect>::new();
+    for e in event_reader.read() {
+        let same = |effect: &StatusEffect| {
+            effect.target == e.target && effect.kind.same_as(&e.effect.kind)
+        };
+        let mut spawned = effects.iter_mut().find(|effect| same(effect));
+        let existing = match spawned.as_mut() {
+            Some(effect) => Some(&mut **effect),
+            None => new_effects.iter_mut().find(|effect| same(effect)),
+        };
+        if existing.is_some() && e.effect.stacking == Stacking::Ignore {
+            continue;
+        }
+
+        let mut data = e.effect;
+        if data.kind.is_crowd_control() {
+            if let Ok(mut diminishing_returns) = diminishing_returns.get_mut(e.target) {
+                let Some(scale) = diminishing_returns.next() else {
+                    continue;
+                };
+                data.duration *= scale;
+            }
+        }
+
+        let new = StatusEffect::new(e.target, &data);
+        match existing {
+            None => new_effects.push(new),
+            Some(effect) => {
+                let stacks = match data.stacking {
+                    Stacking::Stack => (effect.stacks + 1).min(new.max_stacks),
+                    _ => effect.stacks,
+                };
+                *effect = StatusEffect { stacks, ..new };
+            }
+        }
+        event_writer.send(StatusEffectAppliedEvent {
+            target: e.target,
+            kind: data.kind,
+        });
+    }
+    for new in new_effects {
+        commands.spawn((new, StateScoped(GlobalState::InGame)));
+    }
+}
+
+pub fn tick_status_effects(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut effects: Query<(Entity, &mut StatusEffect)>,
+    mut diminishing_returns: Query<&mut DiminishingReturns>,
+    mut event_writer: EventWriter<StatusEffectTickEvent>,
+) {
+    for mut diminishing_returns in diminishing_returns.iter_mut() {
+        diminishing_returns.reset.tick(time.delta());
+        if diminishing_returns.reset.just_finished() {
+            diminishing_returns.applied = 0;
+        }
+    }
+
+    for (entity, mut effect) in effects.iter_mut() {
+        if commands.get_entity(effect.target).is_none() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let (target, kind, stacks) = (effect.target, effect.kind, effect.stacks);
+        if let Some(tick) = effect.tick.as_mut() {
+            tick.tick(time.delta());
+            for _ in 0..tick.times_finished_this_tick() {
+                event_writer.send(StatusEffectTickEvent {
+                    target,
+                    kind,
+                    stacks,
+                });
+            }
+        }
+
+        effect.duration.tick(time.delta());
+        if effect.duration.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn damage_over_time(
+    player: Query<(), With<Player>>,
+    enemy: Query<(), With<BattleEnemy>>,
+    mut event_reader: EventReader<StatusEffectTickEvent>,
+    mut damage_player_event: EventWriter<DamagePlayerEvent>,
+    mut damage_enemy_event: EventWriter<DamageEnemyEvent>,
+) {
+    for e in event_reader.read() {
+        let StatusEffectKind::DamageOverTime {
+            damage,
+            color,
+            damage_type,
+        } = e.kind
+        else {
+            continue;
+        };
+        let damage = damage * e.stacks as f32;
+        // A dying enemy is no longer the `BattleEnemy`.
+        if player.contains(e.target) {
+            damage_player_event.send(DamagePlayerEvent {
+                damage,
+                color,
+                damage_type,
+                crit: false,
+            });
+        } else if enemy.contains(e.target) {
+            damage_enemy_event.send(DamageEnemyEvent {
+                damage,
+                color,
+                damage_type,
+                crit: false,
+            });
+        }
+    }
+}
+
+fn use_charges(
+    mut commands: Commands,
+    mut effects: Query<(Entity, &mut StatusEffect)>,
+    mut event_reader: EventReader<AttackLandedEvent>,
+) {
+    for e in event_reader.read() {
+        for (entity, mut effect) in effects.iter_mut() {
+            if effect.target != e.attacker {
+                continue;
+            }
+            let Some(charges) = effect.charges.as_mut().filter(|charges| 0 < **charges) else {
+                continue;
+            };
+            *charges -= 1;
+            if *charges == 0 {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn end_battle_effects(
+    mut commands: Commands,
+    effects: Query<(Entity, &StatusEffect)>,
+    mut event_reader: EventReader<EnemyDeadEvent>,
+) {
+    for _ in event_reader.read() {
+        for (entity, effect) in effects.iter() {
+            if effect.kind.ends_with_battle() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// Bevy code commonly triggers these lints and they may be important signals
+// about code quality. They are sometimes hard to avoid though, and the CI
+// workflow treats them as errors, so this allows them throughout the project.
+// Feel free to delete this line.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::prelude::*;
+
+pub mod game;
+pub mod sim;
+pub mod ui;
+
+#[derive(States, Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum GlobalState {
+    /// Content catalogs are being loaded.
+    #[default]
+    Loading,
+    MainMenu,
+    InGame,
+}
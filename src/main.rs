@@ -1,47 +1,57 @@
-// Bevy code commonly triggers these lints and they may be important signals
-// about code quality. They are sometimes hard to avoid though, and the CI
-// workflow treats them as errors, so this allows them throughout the project.
-// Feel free to delete this line.
-#![allow(clippy::too_many_arguments, clippy::type_complexity)]
-
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
 
-mod game;
-mod ui;
-
 use bevy::sprite::Wireframe2dPlugin;
 use bevy::window::{WindowResized, WindowResolution};
-use game::GamePlugin;
-use ui::UiPlugin;
+use bevy_jam_5::{
+    game::{
+        replay::ReplayMode,
+        rng::RunSeed,
+        save::{SaveFile, SAVE_FILE},
+        GamePlugin,
+    },
+    ui::UiPlugin,
+    GlobalState,
+};
 
-fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(AssetPlugin {
-                    // Wasm builds will check for meta files (that don't exist) if this isn't set.
-                    // This causes errors and even panics in web builds on itch.
-                    // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                })
-                .set(ImagePlugin::default_nearest()),
-            Wireframe2dPlugin,
-            GamePlugin,
-            UiPlugin,
-        ))
-        .init_state::<GlobalState>()
-        .enable_state_scoped_entities::<GlobalState>()
-        .add_systems(Update, on_window_resize)
-        .run();
+fn arg(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
 }
 
-#[derive(States, Debug, Default, Clone, PartialEq, Eq, Hash)]
-pub enum GlobalState {
-    #[default]
-    MainMenu,
-    InGame,
+fn main() {
+    // `--seed <seed>` replays a run, e.g. one from a bug report.
+    let run_seed = arg("--seed").and_then(|seed| seed.parse().ok());
+    // `--record <file>` saves the run, `--replay <file>` plays it back.
+    let replay_mode = match (arg("--record"), arg("--replay")) {
+        (_, Some(path)) => ReplayMode::Playback(path.into()),
+        (Some(path), None) => ReplayMode::Record(path.into()),
+        (None, None) => ReplayMode::Off,
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(AssetPlugin {
+                // Wasm builds will check for meta files (that don't exist) if this isn't set.
+                // This causes errors and even panics in web builds on itch.
+                // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(ImagePlugin::default_nearest()),
+        Wireframe2dPlugin,
+        GamePlugin,
+        UiPlugin,
+    ))
+    .insert_resource(RunSeed(run_seed))
+    .insert_resource(replay_mode)
+    .init_state::<GlobalState>()
+    .enable_state_scoped_entities::<GlobalState>()
+    .add_systems(Update, on_window_resize);
+    // Wasm builds have no file system to save runs to.
+    #[cfg(not(target_arch = "wasm32"))]
+    app.insert_resource(SaveFile(SAVE_FILE.into()));
+    app.run();
 }
 
 fn on_window_resize(mut ui_scale: ResMut<UiScale>, mut resize_reader: EventReader<WindowResized>) {
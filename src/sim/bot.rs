@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::game::{
+    actions::{PlayerAction, PlayerActionEvent},
+    circle_sectors::{
+        next_section_position, position_to_sector_position, PlayerProgress, SectorIdx,
+        SectorPosition, Sectors, DEFAULT_SECTOR_ID,
+    },
+    data::ContentIds,
+    enemy::BattleEnemy,
+    inventory::Inventory,
+    item_effects::{ItemEffect, ItemTrigger},
+    items::{ItemIdx, Items},
+    player::Player,
+    spells::{Spell, Spells},
+    GameState, Health,
+};
+
+/// Plays in place of the player: decides what to equip, cast and place.
+pub trait BotPolicy: Send + Sync + 'static {
+    /// Called every simulated frame. Actions are applied in order.
+    fn act(&mut self, view: &BotView) -> Vec<PlayerAction>;
+}
+
+/// What a bot can see of the game.
+pub struct BotView<'a> {
+    pub game_state: GameState,
+    pub content_ids: &'a ContentIds,
+    pub items: &'a Items,
+    pub spells: &'a Spells,
+    pub sectors: &'a Sectors,
+    pub inventory: &'a Inventory,
+    pub player_health: &'a Health,
+    /// Health of the enemy in the current battle.
+    pub enemy_health: Option<&'a Health>,
+    pub player_sector: u8,
+    pub cycles: u8,
+    /// Sector placed at each clock position.
+    pub clock: Vec<SectorIdx>,
+}
+
+#[derive(Resource)]
+pub struct Bot(pub Box<dyn BotPolicy>);
+
+/// Where new sectors go on the clock.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum SectorPlacement {
+    /// As far ahead of the player as possible.
+    #[default]
+    Ahead,
+    /// Right in front of the player, so they pay off soon.
+    Near,
+    /// Keep them in the backpack.
+    Never,
+}
+
+/// Equips whatever it finds, casts spells as soon as they are ready and
+/// places new sectors following `sector_placement`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GreedyBot {
+    pub sector_placement: SectorPlacement,
+}
+
+impl GreedyBot {
+    /// Rough value of an item, defense is a damage multiplier.
+    fn item_score(items: &Items, item_idx: ItemIdx) -> f32 {
+        let item = &items[item_idx].item;
+        item.add_damage()
+            + item.add_defense() * 100.0
+            + item.triggers().map(Self::trigger_score).sum::<f32>()
+    }
+
+    /// Rough health a trigger is worth, however often its hook happens.
+    fn trigger_score(trigger: &ItemTrigger) -> f32 {
+        match trigger.effect {
+            ItemEffect::Heal(amount)
+            | ItemEffect::MaxHealth(amount)
+            | ItemEffect::Shield(amount) => amount,
+            // Part of about a battle worth of damage.
+            ItemEffect::LifeSteal(part) | ItemEffect::Thorns(part) => part * 100.0,
+            ItemEffect::StatusEffect { .. } => 0.0,
+        }
+    }
+}
+
+impl BotPolicy for GreedyBot {
+    fn act(&mut self, view: &BotView) -> Vec<PlayerAction> {
+        let mut actions = vec![];
+
+        // Equipping pushes out the last active item, so only do it if
+        // that slot is empty or holds something worse.
+        let pushed_out_score = view
+            .inventory
+            .active_items
+            .iter()
+            .last()
+            .flatten()
+            .map(|item_idx| Self::item_score(view.items, *item_idx));
+        let best_item = view
+            .inventory
+            .backpack_items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item_idx)| Some((i, Self::item_score(view.items, *item_idx?))))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, score)) = best_item {
+            if pushed_out_score.is_none_or(|pushed_out| pushed_out < score) {
+                actions.push(PlayerAction::EquipItem(i));
+            }
+        }
+
+        let spell_slot_free = view
+            .inventory
+            .active_spells
+            .iter()
+            .last()
+            .flatten()
+            .is_none();
+        if spell_slot_free && view.inventory.backpack_spells[0].is_some() {
+            actions.push(PlayerAction::EquipSpell(0));
+        }
+
+        if view.game_state == GameState::Battle {
+            let hurt = view.player_health.percent() < 0.5;
+            for (i, spell_idx) in view.inventory.active_spells.iter().enumerate() {
+                let Some(spell_idx) = spell_idx else {
+                    continue;
+                };
+                let spell_info = &view.spells[*spell_idx];
+                if !spell_info.cooldown.finished() {
+                    continue;
+                }
+                // Save heals for when they matter.
+                if matches!(spell_info.spell, Spell::Heal(_)) && !hurt {
+                    continue;
+                }
+                actions.push(PlayerAction::CastSpell(i));
+            }
+        }
+
+        if self.sector_placement != SectorPlacement::Never
+            && view.inventory.backpack_sectors[0].is_some()
+        {
+            let default_sector = view.content_ids.sectors.get(DEFAULT_SECTOR_ID);
+            let next_sector = next_section_position(view.player_sector);
+            let sectors_num = view.clock.len() as u8;
+            let free = (0..sectors_num).filter(|position| {
+                Some(view.clock[*position as usize]) == default_sector
+                    && *position != view.player_sector
+                    && *position != next_sector
+            });
+            let distance =
+                |position: &u8| (position + sectors_num - view.player_sector) % sectors_num;
+            let target = match self.sector_placement {
+                SectorPlacement::Near => free.min_by_key(distance),
+                _ => free.max_by_key(distance),
+            };
+            if let Some(position) = target {
+                actions.push(PlayerAction::PlaceSector {
+                    backpack: 0,
+                    position,
+                });
+            }
+        }
+
+        actions
+    }
+}
+
+pub fn run_bot(
+    game_state: Res<State<GameState>>,
+    content_ids: Res<ContentIds>,
+    items: Res<Items>,
+    spells: Res<Spells>,
+    sectors: Res<Sectors>,
+    inventory: Res<Inventory>,
+    player_progress: Res<PlayerProgress>,
+    player: Query<(&Transform, &Health), With<Player>>,
+    enemy: Query<&Health, With<BattleEnemy>>,
+    clock: Query<(&SectorPosition, &SectorIdx)>,
+    mut bot: ResMut<Bot>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
+) {
+    let Ok((player_transform, player_health)) = player.get_single() else {
+        return;
+    };
+
+    let mut sector_positions = clock
+        .iter()
+        .map(|(position, sector_idx)| (position.0, *sector_idx))
+        .collect::<Vec<_>>();
+    sector_positions.sort_by_key(|(position, _)| *position);
+
+    let view = BotView {
+        game_state: *game_state.get(),
+        content_ids: content_ids.as_ref(),
+        items: items.as_ref(),
+        spells: spells.as_ref(),
+        sectors: sectors.as_ref(),
+        inventory: inventory.as_ref(),
+        player_health,
+        enemy_health: enemy.get_single().ok(),
+        player_sector: position_to_sector_position(player_transform.translation),
+        cycles: player_progress.cycles,
+        clock: sector_positions.into_iter().map(|(_, idx)| idx).collect(),
+    };
+    event_writer.send_batch(bot.0.act(&view).into_iter().map(PlayerActionEvent));
+}
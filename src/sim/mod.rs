@@ -0,0 +1,288 @@
+//! Plays full runs without a window, renderer, audio or ui.
+//!
+//! The whole `GamePlugin` runs on a fixed simulated clock, so a run takes a
+//! fraction of its real time. A [`BotPolicy`] plays in place of the player.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::AssetMetaCheck, audio::AudioSource, hierarchy::HierarchyPlugin, input::InputPlugin,
+    prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+
+use crate::{
+    game::{
+        chest::ChestOppenedEvent,
+        circle_sectors::{EnemyHardness, PlayerProgress},
+        data::ContentIds,
+        enemy::{BattleEnemy, BattleEnemyDead, EnemyDeadEvent, EnemyIdx},
+        inventory::{Inventory, LootCollectedEvent},
+        loot::{Reward, RewardId},
+        player::Player,
+        rng::{RunRng, RunSeed},
+        GamePlugin, GameState, Health,
+    },
+    ui::{in_game::SelectedSectionButton, setup_ui_style},
+    GlobalState,
+};
+
+pub mod bot;
+
+use bot::{run_bot, Bot, BotPolicy};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimConfig {
+    /// Seed of the run, random if not set.
+    pub seed: Option<u64>,
+    /// Simulated time of one frame.
+    pub time_step: Duration,
+    /// Simulated time after which the run is given up.
+    pub max_time: Duration,
+    /// How often the player hp is recorded.
+    pub hp_sample_interval: Duration,
+    /// Put into the backpack before the run starts.
+    pub start: Vec<RewardId>,
+    pub hardness: EnemyHardness,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            time_step: Duration::from_secs_f64(1.0 / 60.0),
+            max_time: Duration::from_secs(30 * 60),
+            hp_sample_interval: Duration::from_secs(1),
+            start: vec![],
+            hardness: EnemyHardness::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimOutcome {
+    Win,
+    Loss,
+    /// The run did not end within `SimConfig::max_time`.
+    Timeout,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimReport {
+    pub seed: u64,
+    pub outcome: SimOutcome,
+    pub cycles: u8,
+    /// Simulated length of the run.
+    pub duration: Duration,
+    /// Player hp over the run, as (seconds since start, hp).
+    pub hp: Vec<(f32, f32)>,
+    /// Ids of collected rewards, e.g. `item:plant`.
+    pub loot: Vec<String>,
+    /// Ids of items and spells that were ever equipped.
+    pub equipped: Vec<String>,
+    /// Id of every killed enemy with the length of its battle in seconds.
+    pub kills: Vec<(String, f32)>,
+    pub enemies_killed: u32,
+    pub chests_opened: u32,
+}
+
+/// Plays one run with `bot` and reports how it went.
+pub fn simulate(config: &SimConfig, bot: impl BotPolicy) -> SimReport {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            meta_check: AssetMetaCheck::Never,
+            watch_for_changes_override: Some(false),
+            ..default()
+        },
+        StatesPlugin,
+        InputPlugin,
+        HierarchyPlugin,
+        GamePlugin,
+    ))
+    // Assets the renderer, audio and ui plugins would register. Nothing loads
+    // images or sounds, only the handles are needed.
+    .init_asset::<Image>()
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .init_asset::<TextureAtlasLayout>()
+    .init_asset::<AudioSource>()
+    .init_asset::<Font>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(config.time_step))
+    .insert_resource(UiScale(1.0))
+    .insert_resource(SelectedSectionButton(None))
+    .insert_resource(RunSeed(config.seed))
+    .insert_resource(config.hardness.clone())
+    .insert_resource(StartingRewards(config.start.clone()))
+    .insert_resource(Bot(Box::new(bot)))
+    .insert_resource(SimRecord::new(config.hp_sample_interval))
+    .init_state::<GlobalState>()
+    .enable_state_scoped_entities::<GlobalState>()
+    .add_systems(PreStartup, setup_ui_style)
+    .add_systems(OnEnter(GlobalState::MainMenu), start_run)
+    .add_systems(OnEnter(GlobalState::InGame), start_recording)
+    .add_systems(OnEnter(GameState::Win), |mut record: ResMut<SimRecord>| {
+        record.outcome = Some(SimOutcome::Win)
+    })
+    .add_systems(
+        OnEnter(GameState::GameOver),
+        |mut record: ResMut<SimRecord>| record.outcome = Some(SimOutcome::Loss),
+    )
+    .add_systems(
+        Update,
+        (run_bot, record_run).run_if(state_exists::<GameState>),
+    );
+
+    app.finish();
+    app.cleanup();
+    loop {
+        app.update();
+        let elapsed = app.world().resource::<Time>().elapsed();
+        let mut record = app.world_mut().resource_mut::<SimRecord>();
+        if record.outcome.is_none() && config.max_time <= elapsed {
+            record.outcome = Some(SimOutcome::Timeout);
+        }
+        if let Some(outcome) = record.outcome {
+            return SimReport {
+                seed: record.seed,
+                outcome,
+                cycles: record.cycles,
+                duration: record.duration,
+                hp: std::mem::take(&mut record.hp),
+                loot: std::mem::take(&mut record.loot),
+                equipped: std::mem::take(&mut record.equipped),
+                kills: std::mem::take(&mut record.kills),
+                enemies_killed: record.enemies_killed,
+                chests_opened: record.chests_opened,
+            };
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+struct SimRecord {
+    hp_sample_interval: Duration,
+    next_hp_sample: Duration,
+    outcome: Option<SimOutcome>,
+    seed: u64,
+    cycles: u8,
+    duration: Duration,
+    hp: Vec<(f32, f32)>,
+    loot: Vec<String>,
+    equipped: Vec<String>,
+    kills: Vec<(String, f32)>,
+    battle_start: Duration,
+    enemies_killed: u32,
+    chests_opened: u32,
+}
+
+#[derive(Resource, Debug)]
+struct StartingRewards(Vec<RewardId>);
+
+impl SimRecord {
+    fn new(hp_sample_interval: Duration) -> Self {
+        Self {
+            hp_sample_interval,
+            next_hp_sample: Duration::ZERO,
+            outcome: None,
+            seed: 0,
+            cycles: 0,
+            duration: Duration::ZERO,
+            hp: vec![],
+            loot: vec![],
+            equipped: vec![],
+            kills: vec![],
+            battle_start: Duration::ZERO,
+            enemies_killed: 0,
+            chests_opened: 0,
+        }
+    }
+}
+
+/// Skips the main menu as soon as the content is loaded.
+fn start_run(mut global_state: ResMut<NextState<GlobalState>>) {
+    global_state.set(GlobalState::InGame);
+}
+
+fn start_recording(
+    run_rng: Res<RunRng>,
+    content_ids: Res<ContentIds>,
+    starting_rewards: Res<StartingRewards>,
+    mut inventory: ResMut<Inventory>,
+    mut record: ResMut<SimRecord>,
+) {
+    record.seed = run_rng.seed();
+    for reward_id in starting_rewards.0.iter() {
+        if let Some(reward) = reward_id.resolve(&content_ids, "sim") {
+            inventory.add_reward(reward);
+        }
+    }
+}
+
+/// Id of a reward as reported, e.g. `spell:excel`.
+fn reward_id(content_ids: &ContentIds, reward: Reward) -> String {
+    let (kind, id) = match reward {
+        Reward::Item(idx) => ("item", content_ids.items.id(idx)),
+        Reward::Spell(idx) => ("spell", content_ids.spells.id(idx)),
+        Reward::Sector(idx) => ("sector", content_ids.sectors.id(idx)),
+    };
+    format!("{kind}:{}", id.unwrap_or("unknown"))
+}
+
+fn record_run(
+    time: Res<Time>,
+    content_ids: Res<ContentIds>,
+    player_progress: Res<PlayerProgress>,
+    inventory: Res<Inventory>,
+    player: Query<&Health, With<Player>>,
+    battle_started: Query<(), Added<BattleEnemy>>,
+    battle_won: Query<&EnemyIdx, Added<BattleEnemyDead>>,
+    mut record: ResMut<SimRecord>,
+    mut loot_events: EventReader<LootCollectedEvent>,
+    mut enemy_dead_events: EventReader<EnemyDeadEvent>,
+    mut chest_opened_events: EventReader<ChestOppenedEvent>,
+) {
+    let record = record.as_mut();
+    record.duration += time.delta();
+    record.cycles = player_progress.cycles;
+
+    if let Ok(health) = player.get_single() {
+        if record.next_hp_sample <= record.duration {
+            let sample = (record.duration.as_secs_f32(), health.current());
+            record.hp.push(sample);
+            record.next_hp_sample += record.hp_sample_interval;
+        }
+    }
+
+    for e in loot_events.read() {
+        record.loot.push(reward_id(&content_ids, e.0));
+    }
+
+    let active_items = inventory
+        .active_items
+        .iter()
+        .flatten()
+        .map(|i| Reward::Item(*i));
+    let active_spells = inventory
+        .active_spells
+        .iter()
+        .flatten()
+        .map(|i| Reward::Spell(*i));
+    for reward in active_items.chain(active_spells) {
+        let id = reward_id(&content_ids, reward);
+        if !record.equipped.contains(&id) {
+            record.equipped.push(id);
+        }
+    }
+
+    if !battle_started.is_empty() {
+        record.battle_start = record.duration;
+    }
+    for enemy_idx in battle_won.iter() {
+        let id = content_ids.enemies.id(*enemy_idx).unwrap_or("unknown");
+        let time = (record.duration - record.battle_start).as_secs_f32();
+        record.kills.push((id.to_string(), time));
+    }
+    record.enemies_killed += enemy_dead_events.read().count() as u32;
+    record.chests_opened += chest_opened_events.read().count() as u32;
+}
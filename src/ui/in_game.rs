@@ -2,10 +2,12 @@ use bevy::prelude::*;
 
 use crate::{
     game::{
+        actions::{PlayerAction, PlayerActionEvent},
         circle_sectors::{PlayerProgress, SectorPlacedEvent, Sectors, MAX_CYCLES},
         inventory::{Inventory, InventoryUpdateEvent},
         items::Items,
-        spells::{CastSpellEvent, Spells},
+        save::SaveRunEvent,
+        spells::Spells,
         GameState,
     },
     GlobalState,
@@ -885,11 +887,13 @@ fn button_system(
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<Button>, With<InGameButton>),
     >,
+    mut save_event: EventWriter<SaveRunEvent>,
 ) {
     for (interaction, mut color) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *color = ui_style.btn_color_pressed.into();
+                save_event.send(SaveRunEvent);
                 ui_state.set(UiState::MainMenu);
                 global_state.set(GlobalState::MainMenu);
             }
@@ -934,7 +938,7 @@ fn active_items_button_system(
 
                 *tooltip_container_visibility = Visibility::Visible;
                 tooltip_container_item_id.0 = Some(UiItemId::ActiveItemId(*item_id));
-                tooltip_container_text.sections[0].value = item_info.description.into();
+                tooltip_container_text.sections[0].value = item_info.description.clone();
             }
             Interaction::None => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_item_id)) =
@@ -956,17 +960,18 @@ fn active_items_button_system(
 
 fn backpack_items_button_system(
     items: Res<Items>,
-    mut inventory: ResMut<Inventory>,
+    inventory: Res<Inventory>,
     mut interaction_query: Query<(&BackpackItemId, &Interaction), Changed<Interaction>>,
     mut tooltip_text: Query<&mut Text, With<ItemsTooltipText>>,
     mut tooltip_container: Query<(&mut Visibility, &mut ItemsTooltipContainer)>,
-    mut event_writer: EventWriter<InventoryUpdateEvent>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
 ) {
     for (item_id, interaction) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                inventory.equip_item(item_id.0 as usize);
-                event_writer.send(InventoryUpdateEvent);
+                event_writer.send(PlayerActionEvent(PlayerAction::EquipItem(
+                    item_id.0 as usize,
+                )));
             }
             Interaction::Hovered => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_item_id)) =
@@ -986,7 +991,7 @@ fn backpack_items_button_system(
 
                 *tooltip_container_visibility = Visibility::Visible;
                 tooltip_container_item_id.0 = Some(UiItemId::BackpackItemId(*item_id));
-                tooltip_container_text.sections[0].value = item_info.description.into();
+                tooltip_container_text.sections[0].value = item_info.description.clone();
             }
             Interaction::None => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_item_id)) =
@@ -1035,14 +1040,14 @@ fn active_spells_button_system(
     mut interaction_query: Query<(&ActiveSpellId, &Interaction), Changed<Interaction>>,
     mut tooltip_text: Query<&mut Text, With<SpellsTooltipText>>,
     mut tooltip_container: Query<(&mut Visibility, &mut SpellsTooltipContainer)>,
-    mut event_writer: EventWriter<CastSpellEvent>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
 ) {
     for (spell_id, interaction) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                if let Some(spell_idx) = inventory.get_spell_idx(spell_id.0 as usize) {
-                    event_writer.send(CastSpellEvent(spell_idx));
-                }
+                event_writer.send(PlayerActionEvent(PlayerAction::CastSpell(
+                    spell_id.0 as usize,
+                )));
             }
             Interaction::Hovered => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_spell_id)) =
@@ -1062,7 +1067,7 @@ fn active_spells_button_system(
 
                 *tooltip_container_visibility = Visibility::Visible;
                 tooltip_container_spell_id.0 = Some(UiSpellId::ActiveSpellId(*spell_id));
-                tooltip_container_text.sections[0].value = spell_info.description.into();
+                tooltip_container_text.sections[0].value = spell_info.description.clone();
             }
             Interaction::None => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_spell_id)) =
@@ -1084,17 +1089,18 @@ fn active_spells_button_system(
 
 fn backpack_spells_button_system(
     spells: Res<Spells>,
-    mut inventory: ResMut<Inventory>,
+    inventory: Res<Inventory>,
     mut interaction_query: Query<(&BackpackSpellId, &Interaction), Changed<Interaction>>,
     mut tooltip_text: Query<&mut Text, With<SpellsTooltipText>>,
     mut tooltip_container: Query<(&mut Visibility, &mut SpellsTooltipContainer)>,
-    mut event_writer: EventWriter<InventoryUpdateEvent>,
+    mut event_writer: EventWriter<PlayerActionEvent>,
 ) {
     for (spell_id, interaction) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                inventory.equip_spell(spell_id.0 as usize);
-                event_writer.send(InventoryUpdateEvent);
+                event_writer.send(PlayerActionEvent(PlayerAction::EquipSpell(
+                    spell_id.0 as usize,
+                )));
             }
             Interaction::Hovered => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_spell_id)) =
@@ -1114,7 +1120,7 @@ fn backpack_spells_button_system(
 
                 *tooltip_container_visibility = Visibility::Visible;
                 tooltip_container_spell_id.0 = Some(UiSpellId::BackpackSpellId(*spell_id));
-                tooltip_container_text.sections[0].value = spell_info.description.into();
+                tooltip_container_text.sections[0].value = spell_info.description.clone();
             }
             Interaction::None => {
                 let Ok((mut tooltip_container_visibility, mut tooltip_container_spell_id)) =
@@ -1170,7 +1176,7 @@ fn backpack_sectors_button_system(
 
                 *tooltip_container_visibility = Visibility::Visible;
                 tooltip_container_sector_id.0 = Some(*sector_id);
-                tooltip_container_text.sections[0].value = sector_info.description.into();
+                tooltip_container_text.sections[0].value = sector_info.description.clone();
             }
             Interaction::None => {
                 ui_image.color = BUTTON_IMAGE_TINT_DEFAULT;
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::GlobalState;
+use crate::{
+    game::save::{ResumeRun, RunSave, SaveFile},
+    GlobalState,
+};
 
 use super::{spawn_button, UiState, UiStyle};
 
@@ -9,18 +12,42 @@ pub struct MainMenuPlugin;
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(UiState::MainMenu), main_menu_setup);
-        app.add_systems(Update, button_system.run_if(in_state(UiState::MainMenu)));
+        // Buttons only work once the content is loaded.
+        app.add_systems(
+            Update,
+            button_system
+                .run_if(in_state(UiState::MainMenu).and_then(in_state(GlobalState::MainMenu))),
+        );
     }
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum MainMenuButton {
+    Continue,
     Start,
     Settings,
     Exit,
 }
 
-fn main_menu_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
+fn main_menu_setup(
+    mut commands: Commands,
+    ui_style: Res<UiStyle>,
+    save_file: Option<Res<SaveFile>>,
+) {
+    // Saves of other versions are skipped.
+    let can_continue = save_file.is_some_and(|save_file| {
+        if !save_file.0.exists() {
+            return false;
+        }
+        match RunSave::load(&save_file.0) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("can't continue from {}: {e}", save_file.0.display());
+                false
+            }
+        }
+    });
+
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -79,6 +106,9 @@ fn main_menu_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
                     ..default()
                 })
                 .with_children(|builder| {
+                    if can_continue {
+                        spawn_button(builder, &ui_style, MainMenuButton::Continue);
+                    }
                     spawn_button(builder, &ui_style, MainMenuButton::Start);
                     spawn_button(builder, &ui_style, MainMenuButton::Settings);
                     spawn_button(builder, &ui_style, MainMenuButton::Exit);
@@ -88,6 +118,8 @@ fn main_menu_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
 
 fn button_system(
     ui_style: Res<UiStyle>,
+    save_file: Option<Res<SaveFile>>,
+    mut commands: Commands,
     mut ui_state: ResMut<NextState<UiState>>,
     mut global_state: ResMut<NextState<GlobalState>>,
     mut interaction_query: Query<
@@ -101,6 +133,19 @@ fn button_system(
             Interaction::Pressed => {
                 *color = ui_style.btn_color_pressed.into();
                 match button {
+                    MainMenuButton::Continue => {
+                        let Some(save_file) = save_file.as_ref() else {
+                            continue;
+                        };
+                        match RunSave::load(&save_file.0) {
+                            Ok(save) => {
+                                commands.insert_resource(ResumeRun(save));
+                                ui_state.set(UiState::InGame);
+                                global_state.set(GlobalState::InGame);
+                            }
+                            Err(e) => error!("can't continue from {}: {e}", save_file.0.display()),
+                        }
+                    }
                     MainMenuButton::Start => {
                         ui_state.set(UiState::InGame);
                         global_state.set(GlobalState::InGame);
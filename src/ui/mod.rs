@@ -37,7 +37,7 @@ pub struct UiStyle {
     pub text_style: TextStyle,
 }
 
-fn setup_ui_style(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_ui_style(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(UiStyle {
         btn_style: Style {
             margin: UiRect::all(Val::Percent(10.0)),
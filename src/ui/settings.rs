@@ -1,6 +1,6 @@
-use bevy::{audio::Volume, prelude::*};
+use bevy::{prelude::*, ui::RelativeCursorPosition};
 
-use crate::game::sound::{BackgroundMusic, SoundResources};
+use crate::game::settings::{Settings, VolumeChannel};
 
 use super::{spawn_button, UiState, UiStyle};
 
@@ -8,19 +8,122 @@ pub struct SettingsPlugin;
 
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(UiState::Settings), settings_setup);
-        app.add_systems(Update, button_system.run_if(in_state(UiState::Settings)));
+        app.add_systems(
+            OnEnter(UiState::Settings),
+            (settings_setup, update_volume_widgets).chain(),
+        );
+        app.add_systems(
+            Update,
+            (
+                button_system,
+                volume_slider_system,
+                update_volume_widgets.run_if(resource_changed::<Settings>),
+            )
+                .chain()
+                .run_if(in_state(UiState::Settings)),
+        );
     }
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum SettingsButton {
-    VolumeUp,
-    VolumeDown,
+    Mute,
     Exit,
 }
 
-fn settings_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
+/// Track of a volume slider. Pressing or dragging on it sets the volume.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct VolumeSlider(VolumeChannel);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct VolumeSliderFill(VolumeChannel);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct VolumeLabel(VolumeChannel);
+
+fn volume_text(volume: f32) -> String {
+    format!("{:.0}%", volume * 100.0)
+}
+
+fn mute_text(settings: &Settings) -> &'static str {
+    if settings.muted {
+        "Unmute"
+    } else {
+        "Mute"
+    }
+}
+
+fn spawn_volume_slider(
+    builder: &mut ChildBuilder,
+    ui_style: &UiStyle,
+    settings: &Settings,
+    channel: VolumeChannel,
+) {
+    let volume = settings.volume(channel);
+    builder
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                margin: UiRect::all(Val::Percent(2.0)),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Percent(4.0),
+                ..Default::default()
+            },
+            ..default()
+        })
+        .with_children(|builder| {
+            builder.spawn(TextBundle {
+                text: Text::from_section(format!("{:?}", channel), ui_style.text_style.clone()),
+                style: Style {
+                    width: Val::Percent(25.0),
+                    ..Default::default()
+                },
+                ..default()
+            });
+
+            builder
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Percent(50.0),
+                            height: Val::Px(20.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..Default::default()
+                        },
+                        border_color: BorderColor(Color::BLACK),
+                        background_color: ui_style.btn_color_normal.into(),
+                        ..default()
+                    },
+                    RelativeCursorPosition::default(),
+                    VolumeSlider(channel),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(volume * 100.0),
+                                height: Val::Percent(100.0),
+                                ..Default::default()
+                            },
+                            background_color: ui_style.btn_color_pressed.into(),
+                            ..default()
+                        },
+                        VolumeSliderFill(channel),
+                    ));
+                });
+
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section(volume_text(volume), ui_style.text_style.clone()),
+                    ..default()
+                },
+                VolumeLabel(channel),
+            ));
+        });
+}
+
+fn settings_setup(mut commands: Commands, ui_style: Res<UiStyle>, settings: Res<Settings>) {
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -68,7 +171,7 @@ fn settings_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
             builder
                 .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Percent(20.0),
+                        width: Val::Percent(40.0),
                         height: Val::Percent(80.0),
                         flex_direction: FlexDirection::Column,
                         align_self: AlignSelf::Start,
@@ -80,8 +183,10 @@ fn settings_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
                 })
                 .insert(StateScoped(UiState::MainMenu))
                 .with_children(|builder| {
-                    spawn_button(builder, &ui_style, SettingsButton::VolumeUp);
-                    spawn_button(builder, &ui_style, SettingsButton::VolumeDown);
+                    for channel in VolumeChannel::ALL {
+                        spawn_volume_slider(builder, &ui_style, &settings, channel);
+                    }
+                    spawn_button(builder, &ui_style, SettingsButton::Mute);
                     spawn_button(builder, &ui_style, SettingsButton::Exit);
                 });
         });
@@ -89,8 +194,7 @@ fn settings_setup(mut commands: Commands, ui_style: Res<UiStyle>) {
 
 fn button_system(
     ui_style: Res<UiStyle>,
-    music_controller: Query<&AudioSink, With<BackgroundMusic>>,
-    mut sounds: ResMut<SoundResources>,
+    mut settings: ResMut<Settings>,
     mut ui_state: ResMut<NextState<UiState>>,
     mut interaction_query: Query<
         (&SettingsButton, &Interaction, &mut BackgroundColor),
@@ -102,18 +206,7 @@ fn button_system(
             Interaction::Pressed => {
                 *color = ui_style.btn_color_pressed.into();
                 match button {
-                    SettingsButton::VolumeUp => {
-                        if let Ok(sink) = music_controller.get_single() {
-                            sink.set_volume(sink.volume() + 0.1);
-                            sounds.volume = Volume::new(sounds.volume.get() + 0.1);
-                        }
-                    }
-                    SettingsButton::VolumeDown => {
-                        if let Ok(sink) = music_controller.get_single() {
-                            sink.set_volume(sink.volume() - 0.1);
-                            sounds.volume = Volume::new(sounds.volume.get() - 0.1);
-                        }
-                    }
+                    SettingsButton::Mute => settings.muted = !settings.muted,
                     SettingsButton::Exit => {
                         ui_state.set(UiState::MainMenu);
                     }
@@ -128,3 +221,49 @@ fn button_system(
         }
     }
 }
+
+fn volume_slider_system(
+    mut settings: ResMut<Settings>,
+    sliders: Query<(&VolumeSlider, &Interaction, &RelativeCursorPosition)>,
+) {
+    for (slider, interaction, cursor_position) in sliders.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor_position.normalized else {
+            continue;
+        };
+        // Only touch the settings on a change, as every change is saved.
+        let volume = position.x.clamp(0.0, 1.0);
+        if settings.volume(slider.0) != volume {
+            settings.set_volume(slider.0, volume);
+        }
+    }
+}
+
+fn update_volume_widgets(
+    settings: Res<Settings>,
+    buttons: Query<(&SettingsButton, &Children)>,
+    mut fills: Query<(&VolumeSliderFill, &mut Style)>,
+    mut texts: Query<&mut Text>,
+    labels: Query<(Entity, &VolumeLabel)>,
+) {
+    for (fill, mut style) in fills.iter_mut() {
+        style.width = Val::Percent(settings.volume(fill.0) * 100.0);
+    }
+    for (entity, label) in labels.iter() {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.sections[0].value = volume_text(settings.volume(label.0));
+        }
+    }
+    for (button, children) in buttons.iter() {
+        if *button != SettingsButton::Mute {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = mute_text(&settings).to_string();
+            }
+        }
+    }
+}